@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    rpc_url: String,
+    keypair: String,
+    vault: String,
+    streams: Vec<String>,
+    lookup_table: Option<String>,
+    cranker_token_account: Option<String>,
+    #[serde(default = "default_poll_interval_secs")]
+    poll_interval_secs: u64,
+    #[serde(default = "default_max_retries")]
+    max_retries: u32,
+    #[serde(default = "default_confirm_timeout_secs")]
+    confirm_timeout_secs: u64,
+}
+
+fn default_poll_interval_secs() -> u64 {
+    60
+}
+
+fn default_max_retries() -> u32 {
+    5
+}
+
+fn default_confirm_timeout_secs() -> u64 {
+    60
+}
+
+/// Keeper settings, loaded from TOML. See `keeper.example.toml`.
+#[derive(Debug)]
+pub struct Config {
+    pub rpc_url: String,
+    pub keypair: PathBuf,
+    pub vault: Pubkey,
+    pub streams: Vec<Pubkey>,
+    pub lookup_table: Option<Pubkey>,
+    pub cranker_token_account: Option<Pubkey>,
+    pub poll_interval_secs: u64,
+    pub max_retries: u32,
+    pub confirm_timeout_secs: u64,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let raw: RawConfig = toml::from_str(text)?;
+        Ok(Self {
+            rpc_url: raw.rpc_url,
+            keypair: expand_home(&raw.keypair),
+            vault: parse_pubkey("vault", &raw.vault)?,
+            streams: raw
+                .streams
+                .iter()
+                .map(|stream| parse_pubkey("streams", stream))
+                .collect::<Result<_>>()?,
+            lookup_table: raw
+                .lookup_table
+                .map(|key| parse_pubkey("lookup_table", &key))
+                .transpose()?,
+            cranker_token_account: raw
+                .cranker_token_account
+                .map(|key| parse_pubkey("cranker_token_account", &key))
+                .transpose()?,
+            poll_interval_secs: raw.poll_interval_secs,
+            max_retries: raw.max_retries,
+            confirm_timeout_secs: raw.confirm_timeout_secs,
+        })
+    }
+}
+
+fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey> {
+    value
+        .parse()
+        .with_context(|| format!("{field}: invalid pubkey {value:?}"))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,163 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::{bail, ensure, Context, Result};
+use honorary_dammv2_crank_client::lookup_table;
+use honorary_dammv2_crank_client::planner::{PagePlanner, PlannedPage, PlannerConfig};
+use honorary_dammv2_crank_client::rpc::{investors_at, RpcClient};
+use honorary_dammv2_crank_client::schedule::{page_landed, schedule, Schedule};
+use honorary_dammv2_crank_client::Cranker;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_signer::Signer;
+
+use crate::config::Config;
+
+pub struct Keeper {
+    config: Config,
+    rpc: RpcClient,
+    signer: Keypair,
+}
+
+impl Keeper {
+    pub fn new(config: Config) -> Result<Self> {
+        let signer = read_keypair_file(&config.keypair)
+            .map_err(|err| anyhow::anyhow!("reading {}: {err}", config.keypair.display()))?;
+        Ok(Self {
+            rpc: RpcClient::new(config.rpc_url.clone()),
+            config,
+            signer,
+        })
+    }
+
+    /// One pass over the vault: sends any pages due and returns how long to
+    /// sleep before the next pass.
+    pub fn tick(&self) -> Result<Duration> {
+        let poll = Duration::from_secs(self.config.poll_interval_secs);
+        let vault = self
+            .rpc
+            .fetch_vault(&self.config.vault)
+            .context("fetching vault")?;
+        ensure!(
+            vault.policy.transfer_hook_accounts == 0,
+            "vault {} needs transfer hook accounts, which the keeper does not resolve",
+            vault.address
+        );
+        let progress = self.rpc.fetch_progress(&vault.address)?;
+        let now = self.now()?;
+
+        let streams = self
+            .rpc
+            .fetch_streams(&self.config.streams)
+            .context("fetching streams")?;
+        let (investors, locked_total) = investors_at(&self.config.streams, &streams, now);
+        let table = self
+            .config
+            .lookup_table
+            .map(|key| self.rpc.fetch_lookup_table(&key))
+            .transpose()
+            .context("fetching lookup table")?;
+        let pages = PagePlanner {
+            vault: &vault,
+            payer: self.signer.pubkey(),
+            cranker: Some(Cranker {
+                authority: self.signer.pubkey(),
+                token_account: self.config.cranker_token_account,
+            }),
+            lookup_table: table.as_ref(),
+            config: PlannerConfig::default(),
+        }
+        .plan(&investors, locked_total)?;
+
+        match schedule(&progress, now, pages.len()) {
+            Schedule::Wait(secs) => {
+                println!("vault {}: day complete, next day in {secs}s", vault.address);
+                Ok(poll.min(Duration::from_secs(secs)))
+            }
+            Schedule::Run(first) => {
+                println!(
+                    "vault {}: sending pages {first}..{} for {} investors, locked_total {locked_total}",
+                    vault.address,
+                    pages.len(),
+                    investors.len(),
+                );
+                for page in &pages[first..] {
+                    self.send_page(page, table.as_ref())?;
+                }
+                Ok(Duration::ZERO)
+            }
+        }
+    }
+
+    fn send_page(
+        &self,
+        page: &PlannedPage,
+        table: Option<&anchor_lang::solana_program::message::AddressLookupTableAccount>,
+    ) -> Result<()> {
+        let timeout = Duration::from_secs(self.config.confirm_timeout_secs);
+        for attempt in 1..=self.config.max_retries {
+            let blockhash = self.rpc.get_latest_blockhash()?;
+            let tx = lookup_table::page_transaction(
+                page,
+                &self.signer.pubkey(),
+                table,
+                blockhash,
+                &[&self.signer],
+            )?;
+            let result = self.rpc.send_transaction(&tx).and_then(|signature| {
+                Ok((
+                    signature,
+                    self.rpc.confirm_transaction(&signature, timeout)?,
+                ))
+            });
+            match result {
+                Ok((signature, true)) => {
+                    println!("page {} confirmed: {signature}", page.page_index);
+                    return Ok(());
+                }
+                Ok((signature, false)) => eprintln!(
+                    "page {} attempt {attempt}: {signature} not confirmed",
+                    page.page_index
+                ),
+                Err(err) => eprintln!("page {} attempt {attempt}: {err}", page.page_index),
+            }
+
+            let progress = self.rpc.fetch_progress(&self.config.vault)?;
+            if page_landed(&progress, self.now()?, page.page_index as usize) {
+                println!("page {} landed", page.page_index);
+                return Ok(());
+            }
+            sleep(Duration::from_secs(1 << attempt.min(5)));
+        }
+        bail!(
+            "page {} failed after {} attempts",
+            page.page_index,
+            self.config.max_retries
+        )
+    }
+
+    fn now(&self) -> Result<u64> {
+        Ok(self.rpc.get_unix_timestamp()?.max(0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let config = Config::parse(
+            r#"
+            rpc_url = "http://127.0.0.1:8899"
+            keypair = "/tmp/id.json"
+            vault = "11111111111111111111111111111111"
+            streams = ["SysvarC1ock11111111111111111111111111111111"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.streams.len(), 1);
+        assert_eq!(config.max_retries, 5);
+        assert!(config.lookup_table.is_none());
+        assert!(Config::parse("rpc_url = 1").is_err());
+    }
+}
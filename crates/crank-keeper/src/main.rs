@@ -0,0 +1,51 @@
+//! Keeper daemon that runs the daily crank for one vault.
+//!
+//! Each pass reads `Progress` and the clock sysvar, fetches the configured
+//! streams to compute `locked_total`, plans the day's pages and sends those
+//! not yet done, resuming from `Progress.cursor`. Once the day is complete it
+//! polls until the next one opens.
+
+mod config;
+mod keeper;
+
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::Config;
+use crate::keeper::Keeper;
+
+const USAGE: &str = "usage: crank-keeper <config.toml> [--once]";
+
+fn main() -> Result<()> {
+    let mut path = None;
+    let mut once = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--once" => once = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ if path.is_none() => path = Some(PathBuf::from(arg)),
+            _ => bail!(USAGE),
+        }
+    }
+    let config = Config::load(&path.context(USAGE)?)?;
+    let poll = Duration::from_secs(config.poll_interval_secs);
+    let keeper = Keeper::new(config)?;
+
+    loop {
+        match keeper.tick() {
+            Ok(_) if once => return Ok(()),
+            Ok(wait) => sleep(wait),
+            Err(err) if once => return Err(err),
+            Err(err) => {
+                eprintln!("crank failed: {err:#}");
+                sleep(poll);
+            }
+        }
+    }
+}
@@ -0,0 +1,505 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::message::{legacy, VersionedMessage};
+use anchor_lang::AnchorDeserialize;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anyhow::{bail, ensure, Context, Result};
+use honorary_dammv2_crank::{instruction as args, FeeEstimate, Policy, Pool, Progress};
+use honorary_dammv2_crank_client::planner::{PagePlanner, PlannedPage, PlannerConfig};
+use honorary_dammv2_crank_client::preview::{preview, StreamSnapshot};
+use honorary_dammv2_crank_client::rpc::{investors_at, RpcClient, SimulationResult};
+use honorary_dammv2_crank_client::schedule::{schedule, Schedule};
+use honorary_dammv2_crank_client::{instructions, lookup_table, pda, state, Cranker, Vault};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::versioned::VersionedTransaction;
+
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// RPC connection and fee payer shared by every command. The payer also
+/// signs as policy authority and cranker.
+pub struct Operator {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+}
+
+pub struct InitPolicy {
+    pub vault: Keypair,
+    pub pool: Pubkey,
+    pub creator: Option<Pubkey>,
+    pub args: args::InitializePolicy,
+}
+
+pub struct Crank {
+    pub vault: Pubkey,
+    pub streams: Vec<Pubkey>,
+    pub lookup_table: Option<Pubkey>,
+    pub cranker_token_account: Option<Pubkey>,
+    pub dry_run: bool,
+}
+
+pub struct Preview {
+    pub vault: Pubkey,
+    pub streams: Vec<Pubkey>,
+    pub claimed: Option<u64>,
+    pub csv: Option<PathBuf>,
+}
+
+impl Operator {
+    pub fn validate_pool(&self, pool: Pubkey) -> Result<()> {
+        let decoded = self.fetch_pool(&pool)?;
+        let ix = instructions::validate_pool(pool, decoded.token_b_mint);
+        let simulation = self.simulate(&[ix])?;
+        print_logs(&simulation);
+        ensure!(
+            simulation.err.is_none(),
+            "pool {pool} is not quote-only: {}",
+            simulation.err.unwrap_or_default()
+        );
+        println!(
+            "pool {pool} collects fees only in quote mint {}",
+            decoded.token_b_mint
+        );
+        Ok(())
+    }
+
+    /// Creates the owner PDA's base and quote ATAs if missing, then verifies
+    /// them with `initialize_treasury_accounts`.
+    pub fn init_treasuries(&self, vault: Pubkey, pool: Pubkey) -> Result<()> {
+        let decoded = self.fetch_pool(&pool)?;
+        let (base_token_program, quote_token_program) = self.mint_programs(&decoded)?;
+        let owner = pda::owner(&vault);
+        let payer = self.payer.pubkey();
+        let ixs = [
+            create_associated_token_account_idempotent(
+                &payer,
+                &owner,
+                &decoded.token_a_mint,
+                &base_token_program,
+            ),
+            create_associated_token_account_idempotent(
+                &payer,
+                &owner,
+                &decoded.token_b_mint,
+                &quote_token_program,
+            ),
+            instructions::initialize_treasury_accounts(
+                vault,
+                decoded.token_a_mint,
+                decoded.token_b_mint,
+                base_token_program,
+                quote_token_program,
+                payer,
+            ),
+        ];
+        self.send(&ixs, &[])?;
+        println!(
+            "base treasury:  {}",
+            get_associated_token_address_with_program_id(
+                &owner,
+                &decoded.token_a_mint,
+                &base_token_program
+            )
+        );
+        println!(
+            "quote treasury: {}",
+            get_associated_token_address_with_program_id(
+                &owner,
+                &decoded.token_b_mint,
+                &quote_token_program
+            )
+        );
+        Ok(())
+    }
+
+    /// Treasuries are the owner PDA's ATAs created by `init_treasuries`.
+    pub fn init_policy(&self, command: InitPolicy) -> Result<()> {
+        let decoded = self.fetch_pool(&command.pool)?;
+        let (base_token_program, quote_token_program) = self.mint_programs(&decoded)?;
+        let vault = command.vault.pubkey();
+        let owner = pda::owner(&vault);
+        let payer = self.payer.pubkey();
+        let ix = instructions::initialize_policy(
+            vault,
+            command.pool,
+            decoded.token_b_mint,
+            get_associated_token_address_with_program_id(
+                &owner,
+                &decoded.token_a_mint,
+                &base_token_program,
+            ),
+            get_associated_token_address_with_program_id(
+                &owner,
+                &decoded.token_b_mint,
+                &quote_token_program,
+            ),
+            command.creator.unwrap_or(payer),
+            payer,
+            command.args,
+        );
+        self.send(&[ix], &[&command.vault])?;
+        println!("policy: {}", pda::policy(&vault));
+        Ok(())
+    }
+
+    pub fn init_progress(&self, vault: Pubkey) -> Result<()> {
+        self.send(
+            &[instructions::initialize_progress(
+                vault,
+                self.payer.pubkey(),
+            )],
+            &[],
+        )?;
+        println!("progress: {}", pda::progress(&vault));
+        Ok(())
+    }
+
+    /// Opens the honorary position on the policy's pool with a fresh
+    /// position NFT mint.
+    pub fn init_position(&self, vault: Pubkey) -> Result<()> {
+        let policy = state::decode_policy(&self.rpc.get_account_data(&pda::policy(&vault))?)?;
+        let mint = Keypair::new();
+        let ix = instructions::initialize_honorary_position(
+            vault,
+            policy.pool,
+            mint.pubkey(),
+            self.payer.pubkey(),
+        );
+        self.send(&[ix], &[&mint])?;
+        println!("position: {}", pda::damm_position(&mint.pubkey()));
+        println!("position NFT mint: {}", mint.pubkey());
+        Ok(())
+    }
+
+    pub fn show(&self, vault: Pubkey) -> Result<()> {
+        let policy = state::decode_policy(&self.rpc.get_account_data(&pda::policy(&vault))?)?;
+        println!("Policy {}", pda::policy(&vault));
+        print_policy(&policy);
+        match self.rpc.get_account(&pda::progress(&vault))? {
+            Some(account) => {
+                println!();
+                println!("Progress {}", pda::progress(&vault));
+                print_progress(&state::decode_progress(&account.data)?);
+            }
+            None => println!("\nProgress not initialized"),
+        }
+        Ok(())
+    }
+
+    /// Plans today's pages and sends those still due. With `dry_run`, prints
+    /// the plan and simulates the next page instead; later pages depend on
+    /// the ones before them landing, so only the next one can be simulated.
+    pub fn crank(&self, command: Crank) -> Result<()> {
+        let vault = self.rpc.fetch_vault(&command.vault)?;
+        ensure!(
+            vault.policy.transfer_hook_accounts == 0,
+            "vault {} needs transfer hook accounts, which the CLI does not resolve",
+            vault.address
+        );
+        let progress = self.rpc.fetch_progress(&vault.address)?;
+        let now = self.rpc.get_unix_timestamp()?.max(0) as u64;
+        let streams = self.rpc.fetch_streams(&command.streams)?;
+        let (investors, locked_total) = investors_at(&command.streams, &streams, now);
+        let table = command
+            .lookup_table
+            .map(|key| self.rpc.fetch_lookup_table(&key))
+            .transpose()?;
+        let pages = PagePlanner {
+            vault: &vault,
+            payer: self.payer.pubkey(),
+            cranker: Some(Cranker {
+                authority: self.payer.pubkey(),
+                token_account: command.cranker_token_account,
+            }),
+            lookup_table: table.as_ref(),
+            config: PlannerConfig::default(),
+        }
+        .plan(&investors, locked_total)?;
+
+        println!(
+            "{} investors, locked_total {locked_total}, {} pages",
+            investors.len(),
+            pages.len()
+        );
+        for page in &pages {
+            print_page(page);
+        }
+
+        let next = match schedule(&progress, now, pages.len()) {
+            Schedule::Run(next) => next,
+            Schedule::Wait(secs) => {
+                println!("today's distribution is complete; next day opens in {secs}s");
+                return Ok(());
+            }
+        };
+        if command.dry_run {
+            let page = &pages[next];
+            let tx = lookup_table::page_transaction(
+                page,
+                &self.payer.pubkey(),
+                table.as_ref(),
+                self.rpc.get_latest_blockhash()?,
+                &[&self.payer],
+            )?;
+            let simulation = self.rpc.simulate_transaction(&tx)?;
+            print_logs(&simulation);
+            match simulation.err {
+                None => println!(
+                    "page {} simulated successfully, {} compute units",
+                    page.page_index,
+                    simulation.units_consumed.unwrap_or_default()
+                ),
+                Some(err) => bail!("page {} simulation failed: {err}", page.page_index),
+            }
+            return Ok(());
+        }
+
+        for page in &pages[next..] {
+            let tx = lookup_table::page_transaction(
+                page,
+                &self.payer.pubkey(),
+                table.as_ref(),
+                self.rpc.get_latest_blockhash()?,
+                &[&self.payer],
+            )?;
+            let signature = self.rpc.send_transaction(&tx)?;
+            ensure!(
+                self.rpc.confirm_transaction(&signature, CONFIRM_TIMEOUT)?,
+                "page {} not confirmed: {signature}",
+                page.page_index
+            );
+            println!("page {} confirmed: {signature}", page.page_index);
+        }
+        Ok(())
+    }
+
+    /// Previews the next day's payouts for `claimed`, or for the position's
+    /// pending quote fees as reported by `estimate_fees`.
+    pub fn preview(&self, command: Preview) -> Result<()> {
+        let vault = self.rpc.fetch_vault(&command.vault)?;
+        let progress = self.rpc.fetch_progress(&vault.address)?;
+        let claimed = match command.claimed {
+            Some(claimed) => claimed,
+            None => self.pending_quote_fees(&vault)?,
+        };
+        let now = self.rpc.get_unix_timestamp()?.max(0) as u64;
+        let snapshots: Vec<_> = command
+            .streams
+            .iter()
+            .zip(self.rpc.fetch_streams(&command.streams)?)
+            .map(|(key, stream)| StreamSnapshot::new(*key, &stream, now))
+            .collect();
+
+        let day = preview(&vault.policy, &progress, &snapshots, claimed);
+        print!("{day}");
+        if let Some(path) = command.csv {
+            let file =
+                File::create(&path).with_context(|| format!("creating {}", path.display()))?;
+            day.write_csv(BufWriter::new(file))?;
+            println!("wrote {}", path.display());
+        }
+        Ok(())
+    }
+
+    fn pending_quote_fees(&self, vault: &Vault) -> Result<u64> {
+        let simulation = self.simulate(&[vault.estimate_fees()])?;
+        if let Some(err) = simulation.err {
+            bail!("estimate_fees failed: {err}");
+        }
+        let data = simulation
+            .return_data
+            .context("estimate_fees returned no data")?;
+        Ok(FeeEstimate::try_from_slice(&data)?.pending_quote_fees)
+    }
+
+    fn fetch_pool(&self, pool: &Pubkey) -> Result<Box<Pool>> {
+        state::decode_pool(&self.rpc.get_account_data(pool)?)
+            .with_context(|| format!("decoding pool {pool}"))
+    }
+
+    /// Token programs owning the pool's base and quote mints.
+    fn mint_programs(&self, pool: &Pool) -> Result<(Pubkey, Pubkey)> {
+        let mints = self
+            .rpc
+            .get_multiple_accounts(&[pool.token_a_mint, pool.token_b_mint])?;
+        match (&mints[0], &mints[1]) {
+            (Some(base), Some(quote)) => Ok((base.owner, quote.owner)),
+            _ => bail!("pool mints not found"),
+        }
+    }
+
+    fn transaction(
+        &self,
+        ixs: &[Instruction],
+        extra_signers: &[&Keypair],
+    ) -> Result<VersionedTransaction> {
+        let message = legacy::Message::new_with_blockhash(
+            ixs,
+            Some(&self.payer.pubkey()),
+            &self.rpc.get_latest_blockhash()?,
+        );
+        let mut signers = vec![&self.payer];
+        signers.extend_from_slice(extra_signers);
+        Ok(VersionedTransaction::try_new(
+            VersionedMessage::Legacy(message),
+            &signers,
+        )?)
+    }
+
+    fn send(&self, ixs: &[Instruction], extra_signers: &[&Keypair]) -> Result<()> {
+        let signature = self
+            .rpc
+            .send_transaction(&self.transaction(ixs, extra_signers)?)?;
+        ensure!(
+            self.rpc.confirm_transaction(&signature, CONFIRM_TIMEOUT)?,
+            "transaction not confirmed: {signature}"
+        );
+        println!("confirmed: {signature}");
+        Ok(())
+    }
+
+    fn simulate(&self, ixs: &[Instruction]) -> Result<SimulationResult> {
+        Ok(self
+            .rpc
+            .simulate_transaction(&self.transaction(ixs, &[])?)?)
+    }
+}
+
+fn print_page(page: &PlannedPage) {
+    println!(
+        "  page {}: investors {}..{}, {} CU, {} bytes, {} locks{}",
+        page.page_index,
+        page.investors.start,
+        page.investors.end,
+        page.compute_units,
+        page.transaction_size,
+        page.account_locks,
+        if page.is_final_page { ", final" } else { "" }
+    );
+}
+
+fn print_logs(simulation: &SimulationResult) {
+    for log in &simulation.logs {
+        println!("  {log}");
+    }
+}
+
+fn optional<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| "none".to_string(), |value| value.to_string())
+}
+
+fn print_policy(policy: &Policy) {
+    println!("  vault:                   {}", policy.vault);
+    println!("  authority:               {}", policy.authority);
+    println!("  pool:                    {}", policy.pool);
+    println!("  position:                {}", policy.position);
+    println!("  quote_mint:              {}", policy.quote_mint);
+    println!("  base_treasury:           {}", policy.base_treasury);
+    println!("  quote_treasury:          {}", policy.quote_treasury);
+    println!("  creator:                 {}", policy.creator);
+    println!(
+        "  creator_ata:             {}",
+        optional(policy.creator_ata)
+    );
+    println!("  y0:                      {}", policy.y0);
+    println!(
+        "  investor_fee_share_bps:  {}",
+        policy.investor_fee_share_bps
+    );
+    println!("  daily_cap:               {}", optional(policy.daily_cap));
+    println!("  min_payout_lamports:     {}", policy.min_payout_lamports);
+    println!("  operator_fee_bps:        {}", policy.operator_fee_bps);
+    println!(
+        "  operator_fee_recipient:  {}",
+        policy.operator_fee_recipient
+    );
+    println!(
+        "  operator_fee_cap:        {}",
+        optional(policy.operator_fee_cap)
+    );
+    println!("  cranker_reward_fixed:    {}", policy.cranker_reward_fixed);
+    println!("  cranker_reward_bps:      {}", policy.cranker_reward_bps);
+    println!("  cranker_reward_cap:      {}", policy.cranker_reward_cap);
+    println!(
+        "  transfer_hook_accounts:  {}",
+        policy.transfer_hook_accounts
+    );
+    println!("  native_payouts:          {}", policy.native_payouts);
+    println!("  auto_create_atas:        {}", policy.auto_create_atas);
+    println!("  ata_reimbursement:       {}", policy.ata_reimbursement);
+    println!(
+        "  ata_reimbursement_cap:   {}",
+        policy.ata_reimbursement_cap
+    );
+    println!("  locked_permanently:      {}", policy.locked_permanently);
+    println!("  liquidity_token_a:       {}", policy.liquidity_token_a);
+    println!("  liquidity_token_b:       {}", policy.liquidity_token_b);
+    println!("  min_claim_amount:        {}", policy.min_claim_amount);
+    println!(
+        "  min_distribution_amount: {}",
+        policy.min_distribution_amount
+    );
+    println!(
+        "  creator_split_enabled:   {}",
+        policy.creator_split_enabled
+    );
+}
+
+fn print_progress(progress: &Progress) {
+    println!(
+        "  last_distribution_ts:           {}",
+        progress.last_distribution_ts
+    );
+    println!(
+        "  current_day_start_ts:           {}",
+        progress.current_day_start_ts
+    );
+    println!(
+        "  claimed_for_day:                {}",
+        progress.claimed_for_day
+    );
+    println!(
+        "  operator_fee_for_day:           {}",
+        progress.operator_fee_for_day
+    );
+    println!(
+        "  investor_intended_for_day:      {}",
+        progress.investor_intended_for_day
+    );
+    println!(
+        "  creator_share_for_day:          {}",
+        progress.creator_share_for_day
+    );
+    println!(
+        "  actual_distributed:             {}",
+        progress.actual_distributed
+    );
+    println!(
+        "  cranker_rewards_for_day:        {}",
+        progress.cranker_rewards_for_day
+    );
+    println!(
+        "  transfer_fees_for_day:          {}",
+        progress.transfer_fees_for_day
+    );
+    println!(
+        "  investor_transfer_fees_for_day: {}",
+        progress.investor_transfer_fees_for_day
+    );
+    println!(
+        "  ata_reimbursements_for_day:     {}",
+        progress.ata_reimbursements_for_day
+    );
+    println!("  carry_over:                     {}", progress.carry_over);
+    println!(
+        "  deferred_for_day:               {}",
+        progress.deferred_for_day
+    );
+    println!("  cursor:                         {}", progress.cursor);
+}
@@ -0,0 +1,72 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+/// The subset of the Solana CLI config (`solana config get`) the CLI uses.
+#[derive(Debug, Deserialize)]
+pub struct SolanaConfig {
+    #[serde(default = "default_rpc_url")]
+    pub json_rpc_url: String,
+    #[serde(default = "default_keypair_path")]
+    pub keypair_path: String,
+}
+
+fn default_rpc_url() -> String {
+    DEFAULT_RPC_URL.to_string()
+}
+
+fn default_keypair_path() -> String {
+    "~/.config/solana/id.json".to_string()
+}
+
+impl Default for SolanaConfig {
+    fn default() -> Self {
+        Self {
+            json_rpc_url: default_rpc_url(),
+            keypair_path: default_keypair_path(),
+        }
+    }
+}
+
+impl SolanaConfig {
+    /// Loads `path`, or the default Solana CLI config location. A missing
+    /// default config falls back to the Solana CLI defaults.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => (expand_home("~/.config/solana/cli/config.yml"), false),
+        };
+        if !explicit && !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(text)?)
+    }
+}
+
+/// Resolves the Solana CLI cluster monikers, passing URLs through.
+pub fn normalize_url(url: &str) -> String {
+    match url {
+        "m" | "mainnet-beta" => DEFAULT_RPC_URL,
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "t" | "testnet" => "https://api.testnet.solana.com",
+        "l" | "localhost" => "http://127.0.0.1:8899",
+        url => url,
+    }
+    .to_string()
+}
+
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
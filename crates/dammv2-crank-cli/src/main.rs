@@ -0,0 +1,227 @@
+//! Operator CLI for setting up and cranking honorary DAMM v2 vaults.
+//!
+//! The RPC URL and keypair come from the Solana CLI config unless overridden
+//! with `--url` and `--keypair`. The keypair pays fees and signs as policy
+//! authority and cranker.
+
+mod commands;
+mod config;
+
+use std::path::PathBuf;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use honorary_dammv2_crank::instruction as args;
+use honorary_dammv2_crank_client::rpc::RpcClient;
+use solana_keypair::read_keypair_file;
+
+use crate::commands::{Crank, InitPolicy, Operator, Preview};
+use crate::config::{expand_home, normalize_url, SolanaConfig};
+
+#[derive(Parser)]
+#[command(name = "dammv2-crank", version, about)]
+struct Cli {
+    /// Solana CLI config file [default: ~/.config/solana/cli/config.yml]
+    #[arg(short = 'C', long, global = true)]
+    config: Option<PathBuf>,
+    /// RPC URL or moniker (mainnet-beta, devnet, testnet, localhost)
+    #[arg(short, long, global = true)]
+    url: Option<String>,
+    /// Fee payer and authority keypair file
+    #[arg(short, long, global = true)]
+    keypair: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check that a pool collects fees only in its quote mint
+    ValidatePool {
+        #[arg(long)]
+        pool: Pubkey,
+    },
+    /// Create and verify the owner PDA's base and quote treasury ATAs
+    InitTreasuries {
+        #[arg(long)]
+        vault: Pubkey,
+        #[arg(long)]
+        pool: Pubkey,
+    },
+    /// Create the vault policy; run after init-treasuries
+    InitPolicy {
+        /// Vault keypair file; the vault signs its policy's creation
+        #[arg(long)]
+        vault_keypair: PathBuf,
+        #[arg(long)]
+        pool: Pubkey,
+        /// Creator wallet [default: the keypair]
+        #[arg(long)]
+        creator: Option<Pubkey>,
+        /// Creator quote token account [default: any the creator owns]
+        #[arg(long)]
+        creator_ata: Option<Pubkey>,
+        /// Total investor allocation minted at TGE
+        #[arg(long)]
+        y0: u64,
+        #[arg(long)]
+        investor_fee_share_bps: u16,
+        #[arg(long)]
+        daily_cap: Option<u64>,
+        #[arg(long, default_value_t = 0)]
+        min_payout_lamports: u64,
+    },
+    /// Create the vault progress account
+    InitProgress {
+        #[arg(long)]
+        vault: Pubkey,
+    },
+    /// Open the honorary position on the policy's pool
+    InitPosition {
+        #[arg(long)]
+        vault: Pubkey,
+    },
+    /// Print the vault policy and progress
+    Show {
+        #[arg(long)]
+        vault: Pubkey,
+    },
+    /// Plan today's pages and send those still due
+    Crank {
+        #[arg(long)]
+        vault: Pubkey,
+        /// Investor Streamflow streams, in page order
+        #[arg(long = "stream", required = true)]
+        streams: Vec<Pubkey>,
+        #[arg(long)]
+        lookup_table: Option<Pubkey>,
+        /// Quote token account receiving the cranker reward
+        #[arg(long)]
+        cranker_token_account: Option<Pubkey>,
+        /// Print the plan and simulate the next page without sending
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Preview the next day's payout per investor
+    Preview {
+        #[arg(long)]
+        vault: Pubkey,
+        /// Investor Streamflow streams, in page order
+        #[arg(long = "stream", required = true)]
+        streams: Vec<Pubkey>,
+        /// Quote fees to distribute [default: the position's pending fees]
+        #[arg(long)]
+        claimed: Option<u64>,
+        /// Also write the payout table to this CSV file
+        #[arg(long)]
+        csv: Option<PathBuf>,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let solana_config = SolanaConfig::load(cli.config.as_deref())?;
+    let url = normalize_url(cli.url.as_deref().unwrap_or(&solana_config.json_rpc_url));
+    let keypair_path = expand_home(
+        cli.keypair
+            .as_deref()
+            .unwrap_or(&solana_config.keypair_path),
+    );
+    let payer = read_keypair_file(&keypair_path)
+        .map_err(|err| anyhow!("reading {}: {err}", keypair_path.display()))?;
+    let operator = Operator {
+        rpc: RpcClient::new(url),
+        payer,
+    };
+
+    match cli.command {
+        Command::ValidatePool { pool } => operator.validate_pool(pool),
+        Command::InitTreasuries { vault, pool } => operator.init_treasuries(vault, pool),
+        Command::InitPolicy {
+            vault_keypair,
+            pool,
+            creator,
+            creator_ata,
+            y0,
+            investor_fee_share_bps,
+            daily_cap,
+            min_payout_lamports,
+        } => operator.init_policy(InitPolicy {
+            vault: read_keypair_file(&vault_keypair)
+                .map_err(|err| anyhow!("reading {}: {err}", vault_keypair.display()))?,
+            pool,
+            creator,
+            args: args::InitializePolicy {
+                y0,
+                investor_fee_share_bps,
+                daily_cap,
+                min_payout_lamports,
+                creator_ata,
+            },
+        }),
+        Command::InitProgress { vault } => operator.init_progress(vault),
+        Command::InitPosition { vault } => operator.init_position(vault),
+        Command::Show { vault } => operator.show(vault),
+        Command::Crank {
+            vault,
+            streams,
+            lookup_table,
+            cranker_token_account,
+            dry_run,
+        } => operator.crank(Crank {
+            vault,
+            streams,
+            lookup_table,
+            cranker_token_account,
+            dry_run,
+        }),
+        Command::Preview {
+            vault,
+            streams,
+            claimed,
+            csv,
+        } => operator.preview(Preview {
+            vault,
+            streams,
+            claimed,
+            csv,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+        let cli = Cli::try_parse_from([
+            "dammv2-crank",
+            "-u",
+            "localhost",
+            "init-position",
+            "--vault",
+            "11111111111111111111111111111111",
+        ])
+        .unwrap();
+        assert_eq!(normalize_url(&cli.url.unwrap()), "http://127.0.0.1:8899");
+        assert!(matches!(
+            cli.command,
+            Command::InitPosition { vault } if vault == Pubkey::default()
+        ));
+    }
+
+    #[test]
+    fn test_solana_config() {
+        let config = SolanaConfig::parse(
+            "---\njson_rpc_url: \"https://api.devnet.solana.com\"\nwebsocket_url: \"\"\n\
+             keypair_path: /home/ops/.config/solana/id.json\ncommitment: confirmed\n",
+        )
+        .unwrap();
+        assert_eq!(config.json_rpc_url, "https://api.devnet.solana.com");
+        assert_eq!(config.keypair_path, "/home/ops/.config/solana/id.json");
+    }
+}
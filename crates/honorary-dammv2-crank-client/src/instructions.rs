@@ -0,0 +1,571 @@
+//! Instruction builders for every program instruction.
+//!
+//! Setup instructions that run before a policy exists are free functions.
+//! Everything else is a method on [`Vault`], which resolves the policy's
+//! accounts and derives the PDAs, so callers only supply signers and
+//! arguments. Arguments reuse the program's generated `instruction::*`
+//! structs.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+use anchor_spl::token_2022::Token2022;
+use honorary_dammv2_crank::{
+    accounts, damm_v2, instruction as args, streamflow, CreatorSplit, Policy, Pool, Position,
+    UnclaimedPayout, ID, POOL_AUTHORITY,
+};
+
+use crate::pda;
+
+/// Builds a crank program instruction from generated accounts and arguments.
+pub fn instruction(accounts: impl ToAccountMetas, args: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+pub fn validate_pool(pool: Pubkey, quote_mint: Pubkey) -> Instruction {
+    instruction(
+        accounts::ValidatePool { pool, quote_mint },
+        args::ValidatePool {},
+    )
+}
+
+/// `vault` must sign alongside `payer`.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_policy(
+    vault: Pubkey,
+    pool: Pubkey,
+    quote_mint: Pubkey,
+    base_treasury: Pubkey,
+    quote_treasury: Pubkey,
+    creator: Pubkey,
+    payer: Pubkey,
+    args: args::InitializePolicy,
+) -> Instruction {
+    instruction(
+        accounts::InitializePolicy {
+            policy: pda::policy(&vault),
+            vault,
+            owner_pda: pda::owner(&vault),
+            pool,
+            quote_mint,
+            base_treasury,
+            quote_treasury,
+            creator,
+            payer,
+            system_program: System::id(),
+        },
+        args,
+    )
+}
+
+pub fn initialize_progress(vault: Pubkey, payer: Pubkey) -> Instruction {
+    instruction(
+        accounts::InitializeProgress {
+            vault,
+            progress: pda::progress(&vault),
+            payer,
+            system_program: System::id(),
+        },
+        args::InitializeProgress {},
+    )
+}
+
+/// Treasuries are the owner PDA's associated token accounts for the base and
+/// quote mints.
+pub fn initialize_treasury_accounts(
+    vault: Pubkey,
+    token_mint_a: Pubkey,
+    quote_mint: Pubkey,
+    base_token_program: Pubkey,
+    quote_token_program: Pubkey,
+    payer: Pubkey,
+) -> Instruction {
+    let owner = pda::owner(&vault);
+    instruction(
+        accounts::InitializeTreasuryAccounts {
+            vault,
+            owner_pda: owner,
+            token_mint_a,
+            quote_mint,
+            base_treasury: get_associated_token_address_with_program_id(
+                &owner,
+                &token_mint_a,
+                &base_token_program,
+            ),
+            quote_treasury: get_associated_token_address_with_program_id(
+                &owner,
+                &quote_mint,
+                &quote_token_program,
+            ),
+            payer,
+            system_program: System::id(),
+            token_program: quote_token_program,
+        },
+        args::InitializeTreasuryAccounts {},
+    )
+}
+
+/// `position_nft_mint` is a fresh keypair that must also sign.
+pub fn initialize_honorary_position(
+    vault: Pubkey,
+    pool: Pubkey,
+    position_nft_mint: Pubkey,
+    payer: Pubkey,
+) -> Instruction {
+    instruction(
+        accounts::InitializeHonoraryPosition {
+            vault,
+            owner_pda: pda::owner(&vault),
+            policy: pda::policy(&vault),
+            position_nft_mint,
+            position_nft_account: pda::damm_position_nft_account(&position_nft_mint),
+            pool,
+            position: pda::damm_position(&position_nft_mint),
+            pool_authority: POOL_AUTHORITY,
+            payer,
+            token_program: Token2022::id(),
+            system_program: System::id(),
+            event_authority: damm_v2::event_authority(),
+            damm_program: damm_v2::ID,
+        },
+        args::InitializeHonoraryPosition {},
+    )
+}
+
+/// Creates an investor's Streamflow stream funded from `sender`'s associated
+/// token account. `metadata` is the stream account, created beforehand by
+/// the caller; `withdrawor` and `fee_oracle` are Streamflow's accounts for
+/// the target cluster.
+#[allow(clippy::too_many_arguments)]
+pub fn create_stream(
+    payer: Pubkey,
+    sender: Pubkey,
+    metadata: Pubkey,
+    mint: Pubkey,
+    token_program: Pubkey,
+    withdrawor: Pubkey,
+    fee_oracle: Pubkey,
+    args: args::CreateStream,
+) -> Instruction {
+    instruction(
+        accounts::CreateStream {
+            payer,
+            sender,
+            sender_tokens: get_associated_token_address_with_program_id(
+                &sender,
+                &mint,
+                &token_program,
+            ),
+            metadata,
+            escrow_tokens: pda::streamflow_escrow(&metadata),
+            withdrawor,
+            mint,
+            fee_oracle,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+            timelock_program: streamflow::ID,
+            token_program,
+            system_program: System::id(),
+        },
+        args,
+    )
+}
+
+/// An investor's Streamflow stream and the wallet it pays out to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Investor {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+}
+
+/// Account that signs a crank page and pays for ATAs and escrow PDAs, with
+/// an optional quote token account for cranker rewards and reimbursements.
+#[derive(Clone, Copy, Debug)]
+pub struct Cranker {
+    pub authority: Pubkey,
+    pub token_account: Option<Pubkey>,
+}
+
+/// One `crank` call over a slice of the investor list.
+#[derive(Clone, Copy, Debug)]
+pub struct CrankPage<'a> {
+    pub page_index: u16,
+    pub locked_total: u64,
+    pub is_final_page: bool,
+    pub investors: &'a [Investor],
+}
+
+/// A vault's policy together with the pool, position and token program keys
+/// its instructions need.
+#[derive(Clone)]
+pub struct Vault {
+    pub address: Pubkey,
+    pub policy: Policy,
+    pub creator_split: Option<CreatorSplit>,
+    pub token_a_mint: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub position_nft_account: Pubkey,
+    pub base_token_program: Pubkey,
+    pub quote_token_program: Pubkey,
+    /// Extra accounts forwarded to every quote `transfer_checked`, in the
+    /// order the policy's `transfer_hook_accounts` expects.
+    pub transfer_hook_accounts: Vec<AccountMeta>,
+}
+
+impl Vault {
+    pub fn new(
+        address: Pubkey,
+        policy: Policy,
+        pool: &Pool,
+        position: &Position,
+        base_token_program: Pubkey,
+        quote_token_program: Pubkey,
+    ) -> Self {
+        Self {
+            address,
+            policy,
+            creator_split: None,
+            token_a_mint: pool.token_a_mint,
+            token_a_vault: pool.token_a_vault,
+            token_b_vault: pool.token_b_vault,
+            position_nft_account: pda::damm_position_nft_account(&position.nft_mint),
+            base_token_program,
+            quote_token_program,
+            transfer_hook_accounts: Vec::new(),
+        }
+    }
+
+    pub fn policy_address(&self) -> Pubkey {
+        pda::policy(&self.address)
+    }
+
+    pub fn progress_address(&self) -> Pubkey {
+        pda::progress(&self.address)
+    }
+
+    pub fn owner_address(&self) -> Pubkey {
+        pda::owner(&self.address)
+    }
+
+    /// Associated quote token account of `wallet`.
+    pub fn quote_ata(&self, wallet: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(
+            wallet,
+            &self.policy.quote_mint,
+            &self.quote_token_program,
+        )
+    }
+
+    pub fn estimate_fees(&self) -> Instruction {
+        instruction(
+            accounts::EstimateFees {
+                vault: self.address,
+                policy: self.policy_address(),
+                progress: self.progress_address(),
+                pool: self.policy.pool,
+                position: self.policy.position,
+            },
+            args::EstimateFees {},
+        )
+    }
+
+    pub fn set_creator(&self, args: args::SetCreator) -> Instruction {
+        instruction(
+            accounts::SetCreator {
+                vault: self.address,
+                policy: self.policy_address(),
+                authority: self.policy.authority,
+            },
+            args,
+        )
+    }
+
+    /// Passes each recipient's token account so `set_creator_split` can
+    /// check it holds the quote mint.
+    pub fn set_creator_split(&self, args: args::SetCreatorSplit) -> Instruction {
+        let recipient_accounts: Vec<_> = args
+            .recipients
+            .iter()
+            .map(|recipient| AccountMeta::new_readonly(recipient.token_account, false))
+            .collect();
+        let mut ix = instruction(
+            accounts::SetCreatorSplit {
+                vault: self.address,
+                policy: self.policy_address(),
+                creator_split: pda::creator_split(&self.address),
+                quote_mint: self.policy.quote_mint,
+                token_program: self.quote_token_program,
+                authority: self.policy.authority,
+                system_program: System::id(),
+            },
+            args,
+        );
+        ix.accounts.extend(recipient_accounts);
+        ix
+    }
+
+    pub fn set_operator_fee(&self, args: args::SetOperatorFee) -> Instruction {
+        instruction(
+            accounts::SetOperatorFee {
+                vault: self.address,
+                policy: self.policy_address(),
+                authority: self.policy.authority,
+            },
+            args,
+        )
+    }
+
+    pub fn set_cranker_reward(&self, args: args::SetCrankerReward) -> Instruction {
+        instruction(
+            accounts::SetCrankerReward {
+                vault: self.address,
+                policy: self.policy_address(),
+                authority: self.policy.authority,
+            },
+            args,
+        )
+    }
+
+    pub fn set_transfer_hook_accounts(&self, args: args::SetTransferHookAccounts) -> Instruction {
+        instruction(
+            accounts::SetTransferHookAccounts {
+                vault: self.address,
+                policy: self.policy_address(),
+                authority: self.policy.authority,
+            },
+            args,
+        )
+    }
+
+    pub fn set_native_payouts(&self, args: args::SetNativePayouts) -> Instruction {
+        instruction(
+            accounts::SetNativePayouts {
+                vault: self.address,
+                policy: self.policy_address(),
+                authority: self.policy.authority,
+            },
+            args,
+        )
+    }
+
+    /// Both token accounts must be owned by the policy authority.
+    pub fn set_liquidity_accounts(
+        &self,
+        token_a_account: Pubkey,
+        token_b_account: Pubkey,
+    ) -> Instruction {
+        instruction(
+            accounts::SetLiquidityAccounts {
+                vault: self.address,
+                policy: self.policy_address(),
+                authority: self.policy.authority,
+                base_treasury: self.policy.base_treasury,
+                quote_mint: self.policy.quote_mint,
+                token_a_account,
+                token_b_account,
+            },
+            args::SetLiquidityAccounts {},
+        )
+    }
+
+    pub fn set_distribution_thresholds(
+        &self,
+        args: args::SetDistributionThresholds,
+    ) -> Instruction {
+        instruction(
+            accounts::SetDistributionThresholds {
+                vault: self.address,
+                policy: self.policy_address(),
+                authority: self.policy.authority,
+            },
+            args,
+        )
+    }
+
+    pub fn set_ata_creation(&self, args: args::SetAtaCreation) -> Instruction {
+        instruction(
+            accounts::SetAtaCreation {
+                vault: self.address,
+                policy: self.policy_address(),
+                authority: self.policy.authority,
+            },
+            args,
+        )
+    }
+
+    /// Locks all of the position's unlocked liquidity.
+    pub fn permanent_lock_position(&self) -> Instruction {
+        instruction(
+            accounts::PermanentLockPosition {
+                vault: self.address,
+                owner_pda: self.owner_address(),
+                policy: self.policy_address(),
+                authority: self.policy.authority,
+                pool: self.policy.pool,
+                position: self.policy.position,
+                position_nft_account: self.position_nft_account,
+                event_authority: damm_v2::event_authority(),
+                damm_program: damm_v2::ID,
+            },
+            args::PermanentLockPosition {},
+        )
+    }
+
+    pub fn add_liquidity(&self, args: args::AddLiquidity) -> Instruction {
+        let mut ix = instruction(self.modify_liquidity_accounts(), args);
+        ix.accounts
+            .extend(self.transfer_hook_accounts.iter().cloned());
+        ix
+    }
+
+    pub fn remove_liquidity(&self, args: args::RemoveLiquidity) -> Instruction {
+        instruction(self.modify_liquidity_accounts(), args)
+    }
+
+    fn modify_liquidity_accounts(&self) -> accounts::ModifyLiquidity {
+        accounts::ModifyLiquidity {
+            vault: self.address,
+            owner_pda: self.owner_address(),
+            policy: self.policy_address(),
+            authority: self.policy.authority,
+            pool: self.policy.pool,
+            position: self.policy.position,
+            position_nft_account: self.position_nft_account,
+            base_treasury: self.policy.base_treasury,
+            quote_treasury: self.policy.quote_treasury,
+            token_a_account: self.policy.liquidity_token_a,
+            token_b_account: self.policy.liquidity_token_b,
+            token_vault_a: self.token_a_vault,
+            token_vault_b: self.token_b_vault,
+            token_mint_a: self.token_a_mint,
+            quote_mint: self.policy.quote_mint,
+            base_token_program: self.base_token_program,
+            token_program: self.quote_token_program,
+            pool_authority: POOL_AUTHORITY,
+            event_authority: damm_v2::event_authority(),
+            damm_program: damm_v2::ID,
+        }
+    }
+
+    /// Claims a decoded `UnclaimedPayout` into `recipient_token_account`.
+    pub fn claim_unclaimed(
+        &self,
+        unclaimed: &UnclaimedPayout,
+        recipient_token_account: Pubkey,
+    ) -> Instruction {
+        let mut ix = instruction(
+            accounts::ClaimUnclaimed {
+                vault: self.address,
+                owner_pda: self.owner_address(),
+                policy: self.policy_address(),
+                unclaimed: pda::unclaimed(&self.address, &unclaimed.stream),
+                recipient: unclaimed.recipient,
+                payer: unclaimed.payer,
+                treasury: self.policy.quote_treasury,
+                recipient_token_account,
+                quote_mint: self.policy.quote_mint,
+                token_program: self.quote_token_program,
+            },
+            args::ClaimUnclaimed {},
+        );
+        ix.accounts
+            .extend(self.transfer_hook_accounts.iter().cloned());
+        ix
+    }
+
+    /// Builds one `crank` page: the fixed accounts, then the transfer hook
+    /// accounts, one entry per investor and, on the final page, the creator
+    /// split recipients.
+    pub fn crank(&self, cranker: Option<&Cranker>, page: &CrankPage) -> Instruction {
+        let policy = &self.policy;
+        let cranker_key = cranker.map(|cranker| cranker.authority);
+        let mut ix = instruction(
+            accounts::Crank {
+                vault: self.address,
+                owner_pda: self.owner_address(),
+                progress: self.progress_address(),
+                policy: self.policy_address(),
+                base_treasury: policy.base_treasury,
+                treasury: policy.quote_treasury,
+                creator_ata: policy
+                    .creator_ata
+                    .unwrap_or_else(|| self.quote_ata(&policy.creator)),
+                position: policy.position,
+                token_mint_a: self.token_a_mint,
+                quote_mint: policy.quote_mint,
+                base_token_program: self.base_token_program,
+                token_program: self.quote_token_program,
+                token_vault_a: self.token_a_vault,
+                token_vault_b: self.token_b_vault,
+                pool_authority: POOL_AUTHORITY,
+                pool: policy.pool,
+                position_nft_account: self.position_nft_account,
+                event_authority: damm_v2::event_authority(),
+                damm_program: damm_v2::ID,
+                creator_split: self
+                    .creator_split
+                    .as_ref()
+                    .map(|_| pda::creator_split(&self.address)),
+                operator_fee_account: (policy.operator_fee_bps > 0)
+                    .then_some(policy.operator_fee_recipient),
+                cranker_token_account: cranker.and_then(|cranker| cranker.token_account),
+                cranker: cranker_key,
+                native_unwrap: policy
+                    .native_payouts
+                    .then(|| pda::native_unwrap(&self.address)),
+                creator: policy.native_payouts.then_some(policy.creator),
+                system_program: cranker_key.map(|_| System::id()),
+                associated_token_program: (policy.auto_create_atas && cranker_key.is_some())
+                    .then_some(associated_token::ID),
+            },
+            args::Crank {
+                page_index: page.page_index,
+                locked_total: page.locked_total,
+                is_final_page: page.is_final_page,
+            },
+        );
+        ix.accounts
+            .extend(self.transfer_hook_accounts.iter().cloned());
+        for investor in page.investors {
+            ix.accounts.extend(self.investor_entry(investor));
+        }
+        if page.is_final_page {
+            ix.accounts.extend(self.split_accounts());
+        }
+        ix
+    }
+
+    /// Remaining accounts for one investor, laid out as `crank` expects for
+    /// the policy's payout mode.
+    pub fn investor_entry(&self, investor: &Investor) -> Vec<AccountMeta> {
+        let stream = AccountMeta::new_readonly(investor.stream, false);
+        if self.policy.native_payouts {
+            return vec![stream, AccountMeta::new(investor.recipient, false)];
+        }
+        let ata = AccountMeta::new(self.quote_ata(&investor.recipient), false);
+        let unclaimed = AccountMeta::new(pda::unclaimed(&self.address, &investor.stream), false);
+        if self.policy.auto_create_atas {
+            vec![
+                stream,
+                ata,
+                AccountMeta::new_readonly(investor.recipient, false),
+                unclaimed,
+            ]
+        } else {
+            vec![stream, ata, unclaimed]
+        }
+    }
+
+    fn split_accounts(&self) -> Vec<AccountMeta> {
+        self.creator_split
+            .iter()
+            .flat_map(|split| &split.recipients[..split.recipient_count as usize])
+            .map(|recipient| AccountMeta::new(recipient.token_account, false))
+            .collect()
+    }
+}
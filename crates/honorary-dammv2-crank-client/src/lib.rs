@@ -0,0 +1,463 @@
+//! Off-chain client for the honorary DAMM v2 crank program: PDA helpers,
+//! instruction builders, account decoders, the crank page planner, address
+//! lookup table tooling, the page schedule and the distribution preview.
+
+pub mod instructions;
+pub mod lookup_table;
+pub mod pda;
+pub mod planner;
+pub mod preview;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod schedule;
+pub mod state;
+
+pub use instructions::{CrankPage, Cranker, Investor, Vault};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::*;
+    use anchor_lang::Discriminator;
+    use honorary_dammv2_crank::{CreatorSplit, Policy, Pool, Position, SplitRecipient};
+
+    fn vault(policy: Policy) -> Vault {
+        let mut pool: Pool = bytemuck::Zeroable::zeroed();
+        pool.token_a_mint = Pubkey::new_unique();
+        let position: Position = bytemuck::Zeroable::zeroed();
+        Vault::new(
+            Pubkey::new_unique(),
+            policy,
+            &pool,
+            &position,
+            anchor_spl::token::ID,
+            anchor_spl::token_2022::ID,
+        )
+    }
+
+    #[test]
+    fn test_crank_page_layout() {
+        let investors = [
+            Investor {
+                stream: Pubkey::new_unique(),
+                recipient: Pubkey::new_unique(),
+            },
+            Investor {
+                stream: Pubkey::new_unique(),
+                recipient: Pubkey::new_unique(),
+            },
+        ];
+        let cranker = Cranker {
+            authority: Pubkey::new_unique(),
+            token_account: None,
+        };
+        let page = CrankPage {
+            page_index: 0,
+            locked_total: 1,
+            is_final_page: true,
+            investors: &investors,
+        };
+
+        let mut vault = vault(Policy::default());
+        let fixed = vault.crank(None, &page).accounts.len() - 6;
+        assert_eq!(fixed, 27);
+
+        let ix = vault.crank(Some(&cranker), &page);
+        assert_eq!(
+            &ix.data[..8],
+            honorary_dammv2_crank::instruction::Crank::DISCRIMINATOR
+        );
+        let entries = &ix.accounts[fixed..];
+        assert_eq!(entries[0].pubkey, investors[0].stream);
+        assert_eq!(entries[1].pubkey, vault.quote_ata(&investors[0].recipient));
+        assert_eq!(
+            entries[2].pubkey,
+            pda::unclaimed(&vault.address, &investors[0].stream)
+        );
+
+        vault.policy.auto_create_atas = true;
+        let ix = vault.crank(Some(&cranker), &page);
+        assert_eq!(ix.accounts.len(), fixed + 8);
+        assert_eq!(ix.accounts[fixed + 2].pubkey, investors[0].recipient);
+
+        vault.policy.auto_create_atas = false;
+        vault.policy.native_payouts = true;
+        let mut split = CreatorSplit {
+            recipient_count: 2,
+            ..Default::default()
+        };
+        split.recipients[0] = SplitRecipient {
+            token_account: Pubkey::new_unique(),
+            bps: 5000,
+        };
+        split.recipients[1] = SplitRecipient {
+            token_account: Pubkey::new_unique(),
+            bps: 5000,
+        };
+        let last_split_account = split.recipients[1].token_account;
+        vault.creator_split = Some(split);
+        let ix = vault.crank(Some(&cranker), &page);
+        assert_eq!(ix.accounts.len(), fixed + 4 + 2);
+        assert!(ix.accounts[fixed + 1].is_writable);
+        assert_eq!(ix.accounts.last().unwrap().pubkey, last_split_account);
+
+        let ix = vault.crank(
+            Some(&cranker),
+            &CrankPage {
+                is_final_page: false,
+                ..page
+            },
+        );
+        assert_eq!(ix.accounts.len(), fixed + 4);
+    }
+
+    #[test]
+    fn test_page_planner() {
+        use anchor_lang::solana_program::message::AddressLookupTableAccount;
+        use planner::{PagePlanner, PlanError, PlannerConfig, MAX_TRANSACTION_SIZE};
+
+        let investors: Vec<Investor> = (0..12)
+            .map(|_| Investor {
+                stream: Pubkey::new_unique(),
+                recipient: Pubkey::new_unique(),
+            })
+            .collect();
+        let vault = vault(Policy::default());
+        let payer = Pubkey::new_unique();
+        let mut planner = PagePlanner {
+            vault: &vault,
+            payer,
+            cranker: Some(Cranker {
+                authority: payer,
+                token_account: None,
+            }),
+            lookup_table: None,
+            config: PlannerConfig::default(),
+        };
+
+        let legacy = planner.plan(&investors, 1_000).unwrap();
+        assert!(legacy.len() > 1);
+        let mut next = 0;
+        for (i, page) in legacy.iter().enumerate() {
+            assert_eq!(page.page_index as usize, i);
+            assert_eq!(page.is_final_page, i == legacy.len() - 1);
+            assert_eq!(page.investors.start, next);
+            assert!(page.transaction_size <= MAX_TRANSACTION_SIZE);
+            next = page.investors.end;
+        }
+        assert_eq!(next, investors.len());
+
+        let ix = vault.crank(
+            planner.cranker.as_ref(),
+            &CrankPage {
+                page_index: 0,
+                locked_total: 1_000,
+                is_final_page: false,
+                investors: &investors,
+            },
+        );
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: ix.accounts.iter().map(|meta| meta.pubkey).collect(),
+        };
+        planner.lookup_table = Some(&table);
+        let with_table = planner.plan(&investors, 1_000).unwrap();
+        assert!(with_table.len() < legacy.len());
+
+        planner.config.max_compute_units = 100_000;
+        assert!(matches!(
+            planner.plan(&investors, 1_000),
+            Err(PlanError::InvestorDoesNotFit(0))
+        ));
+    }
+
+    #[test]
+    fn test_lookup_table_pages() {
+        use anchor_lang::solana_program::hash::Hash;
+        use anchor_lang::solana_program::message::AddressLookupTableAccount;
+        use planner::{measure, PagePlanner, PlannerConfig};
+        use solana_keypair::Keypair;
+        use solana_signer::Signer;
+
+        let payer = Keypair::new();
+        let cranker = Cranker {
+            authority: payer.pubkey(),
+            token_account: None,
+        };
+        let investors: Vec<Investor> = (0..20)
+            .map(|_| Investor {
+                stream: Pubkey::new_unique(),
+                recipient: Pubkey::new_unique(),
+            })
+            .collect();
+        let vault = vault(Policy::default());
+
+        let addresses = lookup_table::crank_addresses(&vault, Some(&cranker), &investors);
+        assert!(!addresses.contains(&payer.pubkey()));
+        assert!(!addresses.contains(&honorary_dammv2_crank::ID));
+        let mut unique = addresses.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), addresses.len());
+
+        let mut table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: addresses[..40].to_vec(),
+        };
+        let missing = lookup_table::missing(&table, &addresses);
+        assert_eq!(missing, addresses[40..]);
+        let extends = lookup_table::extend(table.key, payer.pubkey(), payer.pubkey(), &missing);
+        assert_eq!(
+            extends.len(),
+            missing
+                .len()
+                .div_ceil(lookup_table::MAX_ADDRESSES_PER_EXTEND)
+        );
+        table.addresses = addresses;
+
+        let planner = PagePlanner {
+            vault: &vault,
+            payer: payer.pubkey(),
+            cranker: Some(cranker),
+            lookup_table: Some(&table),
+            config: PlannerConfig::default(),
+        };
+        let pages = planner.plan(&investors, 1_000).unwrap();
+        for page in &pages {
+            let tx = lookup_table::page_transaction(
+                page,
+                &payer.pubkey(),
+                Some(&table),
+                Hash::default(),
+                &[&payer],
+            )
+            .unwrap();
+            let serialized = bincode::serialize(&tx).unwrap();
+            assert_eq!(serialized.len(), page.transaction_size);
+            assert_eq!(
+                measure(&payer.pubkey(), &page.instructions, Some(&table))
+                    .unwrap()
+                    .0,
+                serialized.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_liquidity_instructions() {
+        use honorary_dammv2_crank::instruction::{AddLiquidity, RemoveLiquidity};
+
+        let mut vault = vault(Policy {
+            base_treasury: Pubkey::new_unique(),
+            quote_treasury: Pubkey::new_unique(),
+            liquidity_token_a: Pubkey::new_unique(),
+            liquidity_token_b: Pubkey::new_unique(),
+            transfer_hook_accounts: 1,
+            ..Default::default()
+        });
+        let hook = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+        vault.transfer_hook_accounts = vec![hook.clone()];
+
+        let add = vault.add_liquidity(AddLiquidity {
+            liquidity_delta: 1_000,
+            token_a_amount_threshold: 10,
+            token_b_amount_threshold: 20,
+        });
+        assert_eq!(&add.data[..8], AddLiquidity::DISCRIMINATOR);
+        assert_eq!(add.accounts.last().unwrap(), &hook);
+        let remove = vault.remove_liquidity(RemoveLiquidity {
+            liquidity_delta: 1_000,
+            token_a_amount_threshold: 0,
+            token_b_amount_threshold: 0,
+        });
+        assert_eq!(&remove.data[..8], RemoveLiquidity::DISCRIMINATOR);
+        assert_eq!(remove.accounts.len() + 1, add.accounts.len());
+        for ix in [&add, &remove] {
+            let keys: Vec<_> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+            assert!(keys.contains(&vault.policy.base_treasury));
+            assert!(keys.contains(&vault.policy.quote_treasury));
+            assert!(keys.contains(&vault.policy.liquidity_token_a));
+            assert!(keys.contains(&vault.policy.liquidity_token_b));
+        }
+    }
+
+    #[test]
+    fn test_initialize_position_signers() {
+        let mint = Pubkey::new_unique();
+        let ix = instructions::initialize_honorary_position(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            mint,
+            Pubkey::new_unique(),
+        );
+        let signers: Vec<_> = ix
+            .accounts
+            .iter()
+            .filter(|meta| meta.is_signer)
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert_eq!(signers.len(), 2);
+        assert!(signers.contains(&mint));
+    }
+
+    #[test]
+    fn test_create_stream() {
+        use honorary_dammv2_crank::instruction::CreateStream;
+
+        let (payer, sender, metadata) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let ix = instructions::create_stream(
+            payer,
+            sender,
+            metadata,
+            Pubkey::new_unique(),
+            anchor_spl::token::ID,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            CreateStream {
+                start_time: 1_700_000_000,
+                net_amount_deposited: 1_000,
+                period: 1,
+                amount_per_period: 10,
+                cliff: 1_700_000_000,
+                cliff_amount: 0,
+                cancelable_by_sender: false,
+                cancelable_by_recipient: false,
+                automatic_withdrawal: false,
+                transferable_by_sender: false,
+                transferable_by_recipient: false,
+                can_topup: false,
+                stream_name: [0; 64],
+                withdraw_frequency: 0,
+                recipient: Pubkey::new_unique(),
+                partner: Pubkey::default(),
+                pausable: false,
+                can_update_rate: false,
+            },
+        );
+        assert_eq!(&ix.data[..8], CreateStream::DISCRIMINATOR);
+        assert_eq!(ix.accounts.len(), 12);
+        assert_eq!(ix.accounts[4].pubkey, pda::streamflow_escrow(&metadata));
+        let signers: Vec<_> = ix
+            .accounts
+            .iter()
+            .filter(|meta| meta.is_signer)
+            .map(|meta| meta.pubkey)
+            .collect();
+        assert_eq!(signers, [payer, sender]);
+    }
+
+    #[test]
+    fn test_schedule() {
+        use honorary_dammv2_crank::Progress;
+        use schedule::{page_landed, schedule, Schedule, DAY_SECONDS};
+
+        let mut progress = Progress::default();
+        assert_eq!(schedule(&progress, 1_000, 3), Schedule::Run(0));
+
+        progress.last_distribution_ts = 1_000;
+        progress.cursor = 1;
+        assert_eq!(schedule(&progress, 1_500, 3), Schedule::Run(1));
+        assert!(page_landed(&progress, 1_500, 0));
+        assert!(!page_landed(&progress, 1_500, 1));
+
+        progress.cursor = 3;
+        assert_eq!(
+            schedule(&progress, 1_500, 3),
+            Schedule::Wait(DAY_SECONDS - 500)
+        );
+        assert_eq!(
+            schedule(&progress, 1_000 + DAY_SECONDS, 3),
+            Schedule::Run(0)
+        );
+        assert!(!page_landed(&progress, 1_000 + DAY_SECONDS, 0));
+
+        progress.cursor = 1;
+        progress.deferred_for_day = true;
+        assert_eq!(
+            schedule(&progress, 1_500, 3),
+            Schedule::Wait(DAY_SECONDS - 500)
+        );
+    }
+
+    #[test]
+    fn test_decode_policy() {
+        let policy = Policy {
+            vault: Pubkey::new_unique(),
+            y0: 42,
+            ..Default::default()
+        };
+        let mut data = Vec::new();
+        policy.try_serialize(&mut data).unwrap();
+        let decoded = state::decode_policy(&data).unwrap();
+        assert_eq!(decoded.vault, policy.vault);
+        assert_eq!(decoded.y0, 42);
+        assert!(state::decode_progress(&data).is_err());
+    }
+
+    #[test]
+    fn test_preview() {
+        use honorary_dammv2_crank::Progress;
+        use preview::{preview, PayoutStatus, StreamSnapshot};
+
+        let mut policy = Policy {
+            y0: 1_000,
+            investor_fee_share_bps: 5_000,
+            min_payout_lamports: 10,
+            operator_fee_bps: 1_000,
+            ..Default::default()
+        };
+        let progress = Progress {
+            carry_over: 100,
+            ..Default::default()
+        };
+        let streams: Vec<_> = [600, 300, 0, 2]
+            .into_iter()
+            .map(|locked| StreamSnapshot {
+                stream: Pubkey::new_unique(),
+                recipient: Pubkey::new_unique(),
+                locked,
+            })
+            .collect();
+
+        let day = preview(&policy, &progress, &streams, 1_000);
+        assert_eq!(day.operator_fee, 100);
+        assert_eq!(day.total_available, 1_000);
+        assert_eq!(day.locked_total, 902);
+        assert_eq!(day.f_locked_bps, 9_020);
+        assert_eq!(day.eligible_bps, 5_000);
+        assert_eq!(day.investor_intended, 500);
+        assert_eq!(day.creator_share, 500);
+        let payouts: Vec<_> = day.payouts.iter().map(|p| (p.payout, p.status)).collect();
+        assert_eq!(
+            payouts,
+            [
+                (332, PayoutStatus::Paid),
+                (166, PayoutStatus::Paid),
+                (0, PayoutStatus::Unlocked),
+                (1, PayoutStatus::Dust),
+            ]
+        );
+        assert_eq!((day.distributed, day.dust, day.carry_over), (498, 1, 2));
+
+        let mut csv = Vec::new();
+        day.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 5);
+        assert!(csv.ends_with(",2,2217,1,dust\n"));
+
+        policy.min_distribution_amount = 2_000;
+        let day = preview(&policy, &progress, &streams, 1_000);
+        assert!(day.deferred && !day.claim_skipped);
+        assert_eq!((day.investor_intended, day.carry_over), (0, 1_000));
+        assert!(day.payouts.iter().all(|p| p.payout == 0));
+
+        policy.min_claim_amount = 2_000;
+        let day = preview(&policy, &progress, &streams, 1_000);
+        assert!(day.claim_skipped && day.deferred);
+        assert_eq!((day.claimed, day.carry_over), (0, 100));
+    }
+}
@@ -0,0 +1,143 @@
+//! Address lookup table per vault for crank pages.
+//!
+//! The table holds every non-signer account a crank page references: the
+//! fixed crank accounts, the creator split recipients and each investor's
+//! entry. Pages compiled against it pay one byte per account, which lifts the
+//! legacy limit of a handful of investors per page. A table only serves
+//! lookups from the slot after it was last extended.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::{Instruction, InstructionError};
+use anchor_lang::solana_program::message::{
+    legacy, v0, AddressLookupTableAccount, CompileError, VersionedMessage,
+};
+use solana_address_lookup_table_interface::instruction as alt_instruction;
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_signer::signers::Signers;
+use solana_signer::SignerError;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::planner::PlannedPage;
+use crate::{CrankPage, Cranker, Investor, Vault};
+
+/// Addresses per `extend_lookup_table` instruction that keep the extend
+/// transaction under the size limit with a separate payer and authority.
+pub const MAX_ADDRESSES_PER_EXTEND: usize = 27;
+
+#[derive(Debug)]
+pub enum TransactionBuildError {
+    Compile(CompileError),
+    Sign(SignerError),
+}
+
+impl fmt::Display for TransactionBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionBuildError::Compile(err) => write!(f, "message compilation failed: {err}"),
+            TransactionBuildError::Sign(err) => write!(f, "signing failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for TransactionBuildError {}
+
+/// Every address crank pages of `vault` reference over `investors`, without
+/// signers or the crank program itself, which must stay static. The order is
+/// stable, so re-running it after the investor set grows only appends.
+pub fn crank_addresses(
+    vault: &Vault,
+    cranker: Option<&Cranker>,
+    investors: &[Investor],
+) -> Vec<Pubkey> {
+    let ix = vault.crank(
+        cranker,
+        &CrankPage {
+            page_index: 0,
+            locked_total: 0,
+            is_final_page: true,
+            investors,
+        },
+    );
+    let mut seen = HashSet::new();
+    ix.accounts
+        .into_iter()
+        .filter(|meta| !meta.is_signer && meta.pubkey != honorary_dammv2_crank::ID)
+        .map(|meta| meta.pubkey)
+        .filter(|address| seen.insert(*address))
+        .collect()
+}
+
+/// Creates a table owned by `authority`. `recent_slot` must be a recent
+/// finalized slot and also determines the table address.
+pub fn create(authority: Pubkey, payer: Pubkey, recent_slot: u64) -> (Instruction, Pubkey) {
+    alt_instruction::create_lookup_table(authority, payer, recent_slot)
+}
+
+/// Extend instructions adding `addresses` to `table`, one per transaction.
+pub fn extend(
+    table: Pubkey,
+    authority: Pubkey,
+    payer: Pubkey,
+    addresses: &[Pubkey],
+) -> Vec<Instruction> {
+    addresses
+        .chunks(MAX_ADDRESSES_PER_EXTEND)
+        .map(|chunk| {
+            alt_instruction::extend_lookup_table(table, authority, Some(payer), chunk.to_vec())
+        })
+        .collect()
+}
+
+/// Addresses from `wanted` the table does not hold yet, in order.
+pub fn missing(table: &AddressLookupTableAccount, wanted: &[Pubkey]) -> Vec<Pubkey> {
+    let present: HashSet<_> = table.addresses.iter().collect();
+    wanted
+        .iter()
+        .filter(|address| !present.contains(address))
+        .copied()
+        .collect()
+}
+
+pub fn decode(
+    key: Pubkey,
+    data: &[u8],
+) -> std::result::Result<AddressLookupTableAccount, InstructionError> {
+    let table = AddressLookupTable::deserialize(data)?;
+    Ok(AddressLookupTableAccount {
+        key,
+        addresses: table.addresses.to_vec(),
+    })
+}
+
+/// Signs a planned page as a v0 transaction resolving accounts through
+/// `table`, or as a legacy transaction without one. `signers` must cover the
+/// payer and the cranker.
+pub fn page_transaction<T: Signers + ?Sized>(
+    page: &PlannedPage,
+    payer: &Pubkey,
+    table: Option<&AddressLookupTableAccount>,
+    recent_blockhash: Hash,
+    signers: &T,
+) -> std::result::Result<VersionedTransaction, TransactionBuildError> {
+    let message = match table {
+        Some(table) => VersionedMessage::V0(
+            v0::Message::try_compile(
+                payer,
+                &page.instructions,
+                std::slice::from_ref(table),
+                recent_blockhash,
+            )
+            .map_err(TransactionBuildError::Compile)?,
+        ),
+        None => VersionedMessage::Legacy(legacy::Message::new_with_blockhash(
+            &page.instructions,
+            Some(payer),
+            &recent_blockhash,
+        )),
+    };
+    VersionedTransaction::try_new(message, signers).map_err(TransactionBuildError::Sign)
+}
@@ -0,0 +1,49 @@
+//! Program derived addresses of the crank program and the DAMM v2 accounts it
+//! touches.
+
+use anchor_lang::prelude::*;
+use honorary_dammv2_crank::{damm_v2, streamflow, ID};
+
+pub fn policy(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"policy", vault.as_ref()], &ID).0
+}
+
+pub fn progress(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"progress", vault.as_ref()], &ID).0
+}
+
+/// Owner of the honorary position and the treasuries.
+pub fn owner(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"investor_fee_pos_owner", vault.as_ref()], &ID).0
+}
+
+pub fn creator_split(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"creator_split", vault.as_ref()], &ID).0
+}
+
+pub fn native_unwrap(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"native_unwrap", vault.as_ref()], &ID).0
+}
+
+pub fn unclaimed(vault: &Pubkey, stream: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"unclaimed", vault.as_ref(), stream.as_ref()], &ID).0
+}
+
+/// DAMM v2 position account for a position NFT mint.
+pub fn damm_position(position_nft_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"position", position_nft_mint.as_ref()], &damm_v2::ID).0
+}
+
+/// DAMM v2 token account holding a position NFT.
+pub fn damm_position_nft_account(position_nft_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"position_nft_account", position_nft_mint.as_ref()],
+        &damm_v2::ID,
+    )
+    .0
+}
+
+/// Streamflow escrow token account of a stream's metadata account.
+pub fn streamflow_escrow(metadata: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"strm", metadata.as_ref()], &streamflow::ID).0
+}
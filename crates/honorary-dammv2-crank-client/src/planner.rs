@@ -0,0 +1,253 @@
+//! Splits an investor list into `crank` pages that fit the transaction size,
+//! account lock and compute limits.
+//!
+//! Each page is measured by compiling its message, so the estimate follows
+//! the exact account set of the policy's payout mode. With an address lookup
+//! table, accounts found in the table cost one byte instead of 32 and pages
+//! are usually bound by the account lock limit instead.
+
+use std::fmt;
+use std::ops::Range;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::message::{v0, AddressLookupTableAccount, CompileError};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+
+use crate::{CrankPage, Cranker, Investor, Vault};
+
+/// Largest serialized transaction the cluster accepts.
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+/// Most accounts a transaction may lock, including lookup table accounts.
+pub const MAX_ACCOUNT_LOCKS: usize = 64;
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Compute unit estimates used to size pages.
+#[derive(Clone, Debug)]
+pub struct PlannerConfig {
+    /// Fixed cost of every page: account validation and progress updates.
+    pub base_compute_units: u32,
+    /// Extra cost of the first page, which claims the day's fees.
+    pub claim_compute_units: u32,
+    /// Cost of one investor payout or creator split transfer.
+    pub compute_units_per_investor: u32,
+    /// Extra cost per investor when the policy creates missing ATAs.
+    pub ata_creation_compute_units: u32,
+    pub max_compute_units: u32,
+}
+
+impl Default for PlannerConfig {
+    fn default() -> Self {
+        Self {
+            base_compute_units: 60_000,
+            claim_compute_units: 80_000,
+            compute_units_per_investor: 30_000,
+            ata_creation_compute_units: 25_000,
+            max_compute_units: MAX_COMPUTE_UNITS,
+        }
+    }
+}
+
+/// One page transaction, ready to sign.
+#[derive(Clone, Debug)]
+pub struct PlannedPage {
+    pub page_index: u16,
+    pub is_final_page: bool,
+    /// Range of the planned investor list covered by this page.
+    pub investors: Range<usize>,
+    pub compute_units: u32,
+    pub transaction_size: usize,
+    pub account_locks: usize,
+    /// Compute unit limit instruction followed by the `crank` instruction.
+    pub instructions: Vec<Instruction>,
+}
+
+#[derive(Debug)]
+pub enum PlanError {
+    /// The investor at this index does not fit on a page on its own.
+    InvestorDoesNotFit(usize),
+    /// The final page does not fit even without investors, usually because
+    /// of the creator split recipients.
+    FinalPageDoesNotFit,
+    TooManyPages,
+    Compile(CompileError),
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlanError::InvestorDoesNotFit(index) => {
+                write!(f, "investor {index} does not fit on a page")
+            }
+            PlanError::FinalPageDoesNotFit => f.write_str("final page does not fit"),
+            PlanError::TooManyPages => f.write_str("more than u16::MAX pages"),
+            PlanError::Compile(err) => write!(f, "message compilation failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+impl From<CompileError> for PlanError {
+    fn from(err: CompileError) -> Self {
+        PlanError::Compile(err)
+    }
+}
+
+pub struct PagePlanner<'a> {
+    pub vault: &'a Vault,
+    /// Fee payer of the page transactions.
+    pub payer: Pubkey,
+    pub cranker: Option<Cranker>,
+    pub lookup_table: Option<&'a AddressLookupTableAccount>,
+    pub config: PlannerConfig,
+}
+
+impl PagePlanner<'_> {
+    /// Plans the day's pages from page 0. Page boundaries only depend on the
+    /// inputs, so a caller resuming mid-day can re-plan and skip pages below
+    /// `Progress.cursor`.
+    pub fn plan(
+        &self,
+        investors: &[Investor],
+        locked_total: u64,
+    ) -> std::result::Result<Vec<PlannedPage>, PlanError> {
+        let mut pages = Vec::new();
+        let mut start = 0;
+        loop {
+            let page_index = u16::try_from(pages.len()).map_err(|_| PlanError::TooManyPages)?;
+            let remaining = start..investors.len();
+            if let Some(page) = self.page(investors, remaining, page_index, locked_total, true)? {
+                pages.push(page);
+                return Ok(pages);
+            }
+
+            let mut best = None;
+            for end in start + 1..=investors.len() {
+                match self.page(investors, start..end, page_index, locked_total, false)? {
+                    Some(page) => best = Some(page),
+                    None => break,
+                }
+            }
+            let page = best.ok_or(if start < investors.len() {
+                PlanError::InvestorDoesNotFit(start)
+            } else {
+                PlanError::FinalPageDoesNotFit
+            })?;
+            start = page.investors.end;
+            pages.push(page);
+        }
+    }
+
+    /// Builds the page over `range`, or `None` if it exceeds a limit.
+    fn page(
+        &self,
+        investors: &[Investor],
+        range: Range<usize>,
+        page_index: u16,
+        locked_total: u64,
+        is_final_page: bool,
+    ) -> std::result::Result<Option<PlannedPage>, PlanError> {
+        let compute_units = self.compute_units(range.len(), page_index, is_final_page);
+        if compute_units > self.config.max_compute_units {
+            return Ok(None);
+        }
+
+        let crank = self.vault.crank(
+            self.cranker.as_ref(),
+            &CrankPage {
+                page_index,
+                locked_total,
+                is_final_page,
+                investors: &investors[range.clone()],
+            },
+        );
+        let instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(compute_units),
+            crank,
+        ];
+        let (transaction_size, account_locks) =
+            measure(&self.payer, &instructions, self.lookup_table)?;
+        if transaction_size > MAX_TRANSACTION_SIZE || account_locks > MAX_ACCOUNT_LOCKS {
+            return Ok(None);
+        }
+
+        Ok(Some(PlannedPage {
+            page_index,
+            is_final_page,
+            investors: range,
+            compute_units,
+            transaction_size,
+            account_locks,
+            instructions,
+        }))
+    }
+
+    fn compute_units(&self, investors: usize, page_index: u16, is_final_page: bool) -> u32 {
+        let config = &self.config;
+        let mut per_investor = config.compute_units_per_investor;
+        if self.vault.policy.auto_create_atas {
+            per_investor += config.ata_creation_compute_units;
+        }
+        let mut transfers = investors as u64 * per_investor as u64;
+        if is_final_page {
+            let split_len = self
+                .vault
+                .creator_split
+                .as_ref()
+                .map_or(1, |split| split.recipient_count as u64);
+            transfers += split_len * config.compute_units_per_investor as u64;
+        }
+        let mut total = config.base_compute_units as u64 + transfers;
+        if page_index == 0 {
+            total += config.claim_compute_units as u64;
+        }
+        total.min(u32::MAX as u64) as u32
+    }
+}
+
+/// Serialized size and account lock count of a transaction with these
+/// instructions, as a v0 transaction if `lookup_table` is set and as a
+/// legacy one otherwise.
+pub fn measure(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_table: Option<&AddressLookupTableAccount>,
+) -> std::result::Result<(usize, usize), CompileError> {
+    let message = v0::Message::try_compile(
+        payer,
+        instructions,
+        lookup_table.map(std::slice::from_ref).unwrap_or_default(),
+        Hash::default(),
+    )?;
+
+    let signatures = message.header.num_required_signatures as usize;
+    let mut size = short_vec_len(signatures) + 64 * signatures;
+    size += 3 + short_vec_len(message.account_keys.len()) + 32 * message.account_keys.len();
+    size += 32 + short_vec_len(message.instructions.len());
+    for ix in &message.instructions {
+        size += 1 + short_vec_len(ix.accounts.len()) + ix.accounts.len();
+        size += short_vec_len(ix.data.len()) + ix.data.len();
+    }
+
+    let mut locks = message.account_keys.len();
+    if lookup_table.is_some() {
+        size += 1 + short_vec_len(message.address_table_lookups.len());
+        for lookup in &message.address_table_lookups {
+            let writable = lookup.writable_indexes.len();
+            let readonly = lookup.readonly_indexes.len();
+            size += 32 + short_vec_len(writable) + writable + short_vec_len(readonly) + readonly;
+            locks += writable + readonly;
+        }
+    }
+    Ok((size, locks))
+}
+
+fn short_vec_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
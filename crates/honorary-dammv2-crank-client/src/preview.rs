@@ -0,0 +1,229 @@
+//! Preview of a day's distribution before cranking.
+//!
+//! [`preview`] replays the math `crank` runs when a new day starts and on
+//! each page: operator fee, `f_locked`, `eligible_bps`, the daily cap,
+//! pro-rata weights, dust and carry-over. It cannot see which investor token
+//! accounts exist, so escrowed payouts show as paid. Cranker rewards and ATA
+//! reimbursements depend on paging and are left inside the creator share.
+//! Token-2022 transfer fees are not deducted.
+
+use std::fmt;
+use std::io;
+
+use anchor_lang::prelude::*;
+use honorary_dammv2_crank::{investor_payout, investor_weight, Policy, Progress, Stream};
+
+/// A stream's locked amount at the time of the preview.
+#[derive(Clone, Debug)]
+pub struct StreamSnapshot {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub locked: u64,
+}
+
+impl StreamSnapshot {
+    pub fn new(stream: Pubkey, decoded: &Stream, now: u64) -> Self {
+        Self {
+            stream,
+            recipient: decoded.recipient,
+            locked: decoded
+                .deposited_amount
+                .saturating_sub(decoded.unlocked_amount(now)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayoutStatus {
+    Paid,
+    /// Below `min_payout_lamports`; carried to the next day.
+    Dust,
+    /// Nothing locked, so `crank` skips the investor.
+    Unlocked,
+}
+
+impl fmt::Display for PayoutStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            PayoutStatus::Paid => "paid",
+            PayoutStatus::Dust => "dust",
+            PayoutStatus::Unlocked => "unlocked",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InvestorPayout {
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub locked: u64,
+    /// Millionths of `locked_total`.
+    pub weight: u64,
+    pub payout: u64,
+    pub status: PayoutStatus,
+}
+
+#[derive(Clone, Debug)]
+pub struct Preview {
+    /// Quote fees claimed; 0 when the claim is skipped.
+    pub claimed: u64,
+    pub claim_skipped: bool,
+    pub operator_fee: u64,
+    /// Carry-over plus the claim, net of the operator fee.
+    pub total_available: u64,
+    /// The day closes without transfers and `total_available` carries over.
+    pub deferred: bool,
+    pub locked_total: u64,
+    pub f_locked_bps: u64,
+    pub eligible_bps: u16,
+    pub investor_intended: u64,
+    /// Before cranker rewards and ATA reimbursements.
+    pub creator_share: u64,
+    pub payouts: Vec<InvestorPayout>,
+    pub distributed: u64,
+    pub dust: u64,
+    /// `Progress.carry_over` once the final page lands.
+    pub carry_over: u64,
+}
+
+/// Previews the next day's distribution of `claimed` quote fees over
+/// `streams`, in page order, starting from `progress.carry_over`.
+pub fn preview(
+    policy: &Policy,
+    progress: &Progress,
+    streams: &[StreamSnapshot],
+    claimed: u64,
+) -> Preview {
+    let claim_skipped = policy.skips_claim(claimed);
+    let claimed = if claim_skipped { 0 } else { claimed };
+    let operator_fee = policy.operator_fee(claimed);
+    let total_available = progress.carry_over + claimed - operator_fee;
+    let locked_total = streams.iter().map(|stream| stream.locked).sum();
+    let deferred = policy.defers_distribution(claim_skipped, total_available);
+    let investor_intended = if deferred {
+        0
+    } else {
+        policy.investor_intended(total_available, locked_total)
+    };
+
+    let payouts: Vec<_> = streams
+        .iter()
+        .map(|snapshot| {
+            let weight = investor_weight(snapshot.locked, locked_total);
+            let payout = investor_payout(investor_intended, weight);
+            let status = if snapshot.locked == 0 {
+                PayoutStatus::Unlocked
+            } else if payout >= policy.min_payout_lamports {
+                PayoutStatus::Paid
+            } else {
+                PayoutStatus::Dust
+            };
+            InvestorPayout {
+                stream: snapshot.stream,
+                recipient: snapshot.recipient,
+                locked: snapshot.locked,
+                weight,
+                payout: if deferred { 0 } else { payout },
+                status,
+            }
+        })
+        .collect();
+    let total_of = |status| {
+        payouts
+            .iter()
+            .filter(|payout| payout.status == status)
+            .map(|payout| payout.payout)
+            .sum::<u64>()
+    };
+    let distributed = total_of(PayoutStatus::Paid);
+    let dust = total_of(PayoutStatus::Dust);
+
+    Preview {
+        claimed,
+        claim_skipped,
+        operator_fee,
+        total_available,
+        deferred,
+        locked_total,
+        f_locked_bps: policy.f_locked_bps(locked_total),
+        eligible_bps: policy.eligible_bps(locked_total),
+        investor_intended,
+        creator_share: if deferred {
+            0
+        } else {
+            total_available.saturating_sub(investor_intended)
+        },
+        distributed,
+        dust,
+        carry_over: if deferred {
+            total_available
+        } else {
+            // Dust is part of what the pages leave undistributed.
+            investor_intended.saturating_sub(distributed)
+        },
+        payouts,
+    }
+}
+
+impl Preview {
+    /// Writes the per-investor table as CSV with a header row.
+    pub fn write_csv(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(writer, "stream,recipient,locked,weight_ppm,payout,status")?;
+        for payout in &self.payouts {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{}",
+                payout.stream,
+                payout.recipient,
+                payout.locked,
+                payout.weight,
+                payout.payout,
+                payout.status
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Preview {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.claim_skipped {
+            writeln!(f, "claimed:           0 (below min_claim_amount, skipped)")?;
+        } else {
+            writeln!(f, "claimed:           {}", self.claimed)?;
+        }
+        writeln!(f, "operator fee:      {}", self.operator_fee)?;
+        writeln!(f, "total available:   {}", self.total_available)?;
+        if self.deferred {
+            writeln!(f, "deferred:          carry_over {}", self.carry_over)?;
+            return Ok(());
+        }
+        writeln!(f, "locked total:      {}", self.locked_total)?;
+        writeln!(f, "f_locked:          {} bps", self.f_locked_bps)?;
+        writeln!(f, "eligible:          {} bps", self.eligible_bps)?;
+        writeln!(f, "investor intended: {}", self.investor_intended)?;
+        writeln!(f, "creator share:     {}", self.creator_share)?;
+        writeln!(f, "distributed:       {}", self.distributed)?;
+        writeln!(f, "dust:              {}", self.dust)?;
+        writeln!(f, "carry over:        {}", self.carry_over)?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<44} {:<44} {:>20} {:>10} {:>20} status",
+            "stream", "recipient", "locked", "weight", "payout"
+        )?;
+        for payout in &self.payouts {
+            writeln!(
+                f,
+                "{:<44} {:<44} {:>20} {:>10} {:>20} {}",
+                payout.stream.to_string(),
+                payout.recipient.to_string(),
+                payout.locked,
+                payout.weight,
+                payout.payout,
+                payout.status
+            )?;
+        }
+        Ok(())
+    }
+}
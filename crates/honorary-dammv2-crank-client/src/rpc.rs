@@ -0,0 +1,397 @@
+//! Minimal blocking JSON-RPC client covering the calls the keeper and the
+//! operator CLI make, plus fetchers that assemble a [`Vault`] from chain
+//! state. Enabled by the `rpc` feature.
+
+use std::fmt;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::message::AddressLookupTableAccount;
+use anchor_lang::solana_program::sysvar;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use honorary_dammv2_crank::{ErrorCode, Progress, Stream, STREAMFLOW_PROGRAM_ID};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::{lookup_table, pda, state, Investor, Vault};
+
+#[derive(Debug)]
+pub enum RpcError {
+    Transport(Box<ureq::Error>),
+    /// Error object returned by the node.
+    Rpc {
+        code: i64,
+        message: String,
+        data: Option<Value>,
+    },
+    Decode(String),
+    AccountNotFound(Pubkey),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RpcError::Transport(err) => write!(f, "rpc transport error: {err}"),
+            RpcError::Rpc { code, message, .. } => write!(f, "rpc error {code}: {message}"),
+            RpcError::Decode(err) => write!(f, "rpc decode error: {err}"),
+            RpcError::AccountNotFound(key) => write!(f, "account {key} not found"),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+impl From<ureq::Error> for RpcError {
+    fn from(err: ureq::Error) -> Self {
+        RpcError::Transport(Box::new(err))
+    }
+}
+
+impl From<anchor_lang::error::Error> for RpcError {
+    fn from(err: anchor_lang::error::Error) -> Self {
+        RpcError::Decode(err.to_string())
+    }
+}
+
+pub type RpcResult<T> = std::result::Result<T, RpcError>;
+
+#[derive(Clone, Debug)]
+pub struct RpcAccount {
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SimulationResult {
+    /// Transaction error, `None` on success.
+    pub err: Option<Value>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    /// Data set with `set_return_data` by the last program that did.
+    pub return_data: Option<Vec<u8>>,
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    result: Option<T>,
+    error: Option<ErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct ErrorObject {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct WithContext<T> {
+    value: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UiAccount {
+    owner: String,
+    lamports: u64,
+    data: (String, String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UiSimulation {
+    err: Option<Value>,
+    logs: Option<Vec<String>>,
+    units_consumed: Option<u64>,
+    return_data: Option<UiReturnData>,
+}
+
+#[derive(Deserialize)]
+struct UiReturnData {
+    data: (String, String),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UiSignatureStatus {
+    err: Option<Value>,
+    confirmation_status: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UiBlockhash {
+    blockhash: String,
+}
+
+/// Most keys `getMultipleAccounts` accepts per call.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+pub struct RpcClient {
+    url: String,
+    agent: ureq::Agent,
+    commitment: &'static str,
+}
+
+impl RpcClient {
+    /// Client reading and confirming at `confirmed` commitment.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_secs(30))
+                .build(),
+            commitment: "confirmed",
+        }
+    }
+
+    fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> RpcResult<T> {
+        let response: Response<T> = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))?
+            .into_json()
+            .map_err(|err| RpcError::Decode(err.to_string()))?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(RpcError::Rpc {
+                code: error.code,
+                message: error.message,
+                data: error.data,
+            }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(RpcError::Decode(format!("{method}: empty response"))),
+        }
+    }
+
+    pub fn get_account(&self, key: &Pubkey) -> RpcResult<Option<RpcAccount>> {
+        let response: WithContext<Option<UiAccount>> = self.request(
+            "getAccountInfo",
+            json!([key.to_string(), {"encoding": "base64", "commitment": self.commitment}]),
+        )?;
+        response.value.map(decode_account).transpose()
+    }
+
+    /// Account data of `key`, failing if the account does not exist.
+    pub fn get_account_data(&self, key: &Pubkey) -> RpcResult<Vec<u8>> {
+        self.get_account(key)?
+            .map(|account| account.data)
+            .ok_or(RpcError::AccountNotFound(*key))
+    }
+
+    pub fn get_multiple_accounts(&self, keys: &[Pubkey]) -> RpcResult<Vec<Option<RpcAccount>>> {
+        let mut accounts = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let keys: Vec<String> = chunk.iter().map(Pubkey::to_string).collect();
+            let response: WithContext<Vec<Option<UiAccount>>> = self.request(
+                "getMultipleAccounts",
+                json!([keys, {"encoding": "base64", "commitment": self.commitment}]),
+            )?;
+            for account in response.value {
+                accounts.push(account.map(decode_account).transpose()?);
+            }
+        }
+        Ok(accounts)
+    }
+
+    pub fn get_slot(&self) -> RpcResult<u64> {
+        self.request("getSlot", json!([{"commitment": self.commitment}]))
+    }
+
+    pub fn get_latest_blockhash(&self) -> RpcResult<Hash> {
+        let response: WithContext<UiBlockhash> = self.request(
+            "getLatestBlockhash",
+            json!([{"commitment": self.commitment}]),
+        )?;
+        response
+            .value
+            .blockhash
+            .parse()
+            .map_err(|_| RpcError::Decode("invalid blockhash".to_string()))
+    }
+
+    /// Cluster time from the clock sysvar, the clock `crank` compares against.
+    pub fn get_unix_timestamp(&self) -> RpcResult<i64> {
+        let data = self.get_account_data(&sysvar::clock::ID)?;
+        // Clock: slot, epoch_start_timestamp, epoch, leader_schedule_epoch,
+        // unix_timestamp, all 8 bytes wide.
+        data.get(32..40)
+            .map(|bytes| i64::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or_else(|| RpcError::Decode("invalid clock sysvar".to_string()))
+    }
+
+    pub fn send_transaction(&self, tx: &VersionedTransaction) -> RpcResult<Signature> {
+        let signature: String = self.request(
+            "sendTransaction",
+            json!([encode_transaction(tx)?, {
+                "encoding": "base64",
+                "preflightCommitment": self.commitment,
+            }]),
+        )?;
+        signature
+            .parse()
+            .map_err(|_| RpcError::Decode("invalid signature".to_string()))
+    }
+
+    /// Simulates without checking signatures, so unsigned transactions work.
+    pub fn simulate_transaction(&self, tx: &VersionedTransaction) -> RpcResult<SimulationResult> {
+        let response: WithContext<UiSimulation> = self.request(
+            "simulateTransaction",
+            json!([encode_transaction(tx)?, {
+                "encoding": "base64",
+                "commitment": self.commitment,
+                "sigVerify": false,
+                "replaceRecentBlockhash": true,
+            }]),
+        )?;
+        let simulation = response.value;
+        Ok(SimulationResult {
+            err: simulation.err,
+            logs: simulation.logs.unwrap_or_default(),
+            units_consumed: simulation.units_consumed,
+            return_data: simulation
+                .return_data
+                .map(|data| decode_base64(&data.data.0))
+                .transpose()?,
+        })
+    }
+
+    /// Polls until the transaction is confirmed or `timeout` passes. Returns
+    /// `Ok(false)` on timeout and the transaction error if it failed.
+    pub fn confirm_transaction(&self, signature: &Signature, timeout: Duration) -> RpcResult<bool> {
+        let start = Instant::now();
+        loop {
+            let response: WithContext<Vec<Option<UiSignatureStatus>>> =
+                self.request("getSignatureStatuses", json!([[signature.to_string()]]))?;
+            if let Some(Some(status)) = response.value.into_iter().next() {
+                if let Some(err) = status.err {
+                    return Err(RpcError::Rpc {
+                        code: 0,
+                        message: format!("transaction {signature} failed"),
+                        data: Some(err),
+                    });
+                }
+                if matches!(
+                    status.confirmation_status.as_deref(),
+                    Some("confirmed" | "finalized")
+                ) {
+                    return Ok(true);
+                }
+            }
+            if start.elapsed() >= timeout {
+                return Ok(false);
+            }
+            sleep(Duration::from_millis(500));
+        }
+    }
+
+    /// Loads the vault's policy, pool, position, creator split and mint
+    /// token programs.
+    pub fn fetch_vault(&self, vault: &Pubkey) -> RpcResult<Vault> {
+        let policy = state::decode_policy(&self.get_account_data(&pda::policy(vault))?)?;
+        let pool = state::decode_pool(&self.get_account_data(&policy.pool)?)?;
+        let position = state::decode_position(&self.get_account_data(&policy.position)?)?;
+        let mints = self.get_multiple_accounts(&[pool.token_a_mint, policy.quote_mint])?;
+        let base_token_program = mints[0]
+            .as_ref()
+            .ok_or(RpcError::AccountNotFound(pool.token_a_mint))?
+            .owner;
+        let quote_token_program = mints[1]
+            .as_ref()
+            .ok_or(RpcError::AccountNotFound(policy.quote_mint))?
+            .owner;
+
+        let mut vault = Vault::new(
+            *vault,
+            policy,
+            &pool,
+            &position,
+            base_token_program,
+            quote_token_program,
+        );
+        vault.creator_split = self
+            .get_account(&pda::creator_split(&vault.address))?
+            .map(|account| state::decode_creator_split(&account.data))
+            .transpose()?;
+        Ok(vault)
+    }
+
+    pub fn fetch_progress(&self, vault: &Pubkey) -> RpcResult<Progress> {
+        Ok(state::decode_progress(
+            &self.get_account_data(&pda::progress(vault))?,
+        )?)
+    }
+
+    /// Decodes `streams` in order, rejecting accounts `crank` would reject.
+    pub fn fetch_streams(&self, streams: &[Pubkey]) -> RpcResult<Vec<Stream>> {
+        streams
+            .iter()
+            .zip(self.get_multiple_accounts(streams)?)
+            .map(|(key, account)| {
+                let account = account.ok_or(RpcError::AccountNotFound(*key))?;
+                if account.owner != STREAMFLOW_PROGRAM_ID {
+                    return Err(error!(ErrorCode::InvalidStream).into());
+                }
+                Ok(state::decode_stream(&account.data)?)
+            })
+            .collect()
+    }
+
+    pub fn fetch_lookup_table(&self, key: &Pubkey) -> RpcResult<AddressLookupTableAccount> {
+        lookup_table::decode(*key, &self.get_account_data(key)?)
+            .map_err(|err| RpcError::Decode(err.to_string()))
+    }
+}
+
+/// Investors and the `locked_total` to pass to `crank` for these streams at
+/// `now`, using the same unlock schedule as the program.
+pub fn investors_at(streams: &[Pubkey], decoded: &[Stream], now: u64) -> (Vec<Investor>, u64) {
+    let investors = streams
+        .iter()
+        .zip(decoded)
+        .map(|(stream, decoded)| Investor {
+            stream: *stream,
+            recipient: decoded.recipient,
+        })
+        .collect();
+    let locked_total = decoded
+        .iter()
+        .map(|stream| {
+            stream
+                .deposited_amount
+                .saturating_sub(stream.unlocked_amount(now))
+        })
+        .sum();
+    (investors, locked_total)
+}
+
+fn decode_account(account: UiAccount) -> RpcResult<RpcAccount> {
+    Ok(RpcAccount {
+        owner: account
+            .owner
+            .parse()
+            .map_err(|_| RpcError::Decode("invalid account owner".to_string()))?,
+        lamports: account.lamports,
+        data: decode_base64(&account.data.0)?,
+    })
+}
+
+fn decode_base64(data: &str) -> RpcResult<Vec<u8>> {
+    BASE64_STANDARD
+        .decode(data)
+        .map_err(|err| RpcError::Decode(err.to_string()))
+}
+
+fn encode_transaction(tx: &VersionedTransaction) -> RpcResult<String> {
+    let bytes = bincode::serialize(tx).map_err(|err| RpcError::Decode(err.to_string()))?;
+    Ok(BASE64_STANDARD.encode(bytes))
+}
@@ -0,0 +1,39 @@
+//! When the next `crank` page is due, derived from the vault's progress and
+//! the cluster time the same way the program gates a distribution day.
+
+use honorary_dammv2_crank::Progress;
+
+/// Length of a distribution day, as enforced by `crank`.
+pub const DAY_SECONDS: u64 = 86_400;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Schedule {
+    /// Send the day's pages from this index on.
+    Run(usize),
+    /// Today's distribution is done; the next day opens in this many seconds.
+    Wait(u64),
+}
+
+/// Decides the next step from the vault's progress, the cluster time and the
+/// number of pages planned for today.
+pub fn schedule(progress: &Progress, now: u64, page_count: usize) -> Schedule {
+    if starts_new_day(progress, now) {
+        return Schedule::Run(0);
+    }
+    if progress.deferred_for_day || progress.cursor as usize >= page_count {
+        Schedule::Wait(progress.last_distribution_ts + DAY_SECONDS - now)
+    } else {
+        Schedule::Run(progress.cursor as usize)
+    }
+}
+
+/// Whether the next `crank` call opens a new distribution day.
+pub fn starts_new_day(progress: &Progress, now: u64) -> bool {
+    progress.last_distribution_ts == 0 || now >= progress.last_distribution_ts + DAY_SECONDS
+}
+
+/// Whether `page_index` already landed today, e.g. after a confirmation
+/// timeout or a crash between sending and confirming.
+pub fn page_landed(progress: &Progress, now: u64, page_index: usize) -> bool {
+    !starts_new_day(progress, now) && progress.cursor as usize > page_index
+}
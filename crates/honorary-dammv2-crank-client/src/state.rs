@@ -0,0 +1,36 @@
+//! Decoders for fetched account data.
+
+use anchor_lang::prelude::*;
+use honorary_dammv2_crank::{
+    parse_damm_account, CreatorSplit, ErrorCode, Policy, Pool, Position, Progress, Stream,
+    UnclaimedPayout, POOL_DISCRIMINATOR, POSITION_DISCRIMINATOR,
+};
+
+pub fn decode_policy(data: &[u8]) -> Result<Policy> {
+    Policy::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_progress(data: &[u8]) -> Result<Progress> {
+    Progress::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_creator_split(data: &[u8]) -> Result<CreatorSplit> {
+    CreatorSplit::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_unclaimed(data: &[u8]) -> Result<UnclaimedPayout> {
+    UnclaimedPayout::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a Streamflow stream the same way `crank` does.
+pub fn decode_stream(data: &[u8]) -> Result<Stream> {
+    Stream::parse(data)
+}
+
+pub fn decode_pool(data: &[u8]) -> Result<Box<Pool>> {
+    parse_damm_account(data, POOL_DISCRIMINATOR, ErrorCode::InvalidPool)
+}
+
+pub fn decode_position(data: &[u8]) -> Result<Box<Position>> {
+    parse_damm_account(data, POSITION_DISCRIMINATOR, ErrorCode::InvalidPosition)
+}
@@ -0,0 +1,204 @@
+//! Typed instruction builders for the Meteora DAMM v2 program.
+//!
+//! `accounts` structs produce the account metas in program order and
+//! `instruction` structs carry the Anchor discriminator with the arguments, so
+//! the same types back the on-chain CPIs and off-chain clients.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+
+pub use crate::{DAMM_V2_PROGRAM_ID as ID, POOL_AUTHORITY};
+
+/// Builds a DAMM v2 instruction from typed accounts and arguments.
+pub fn instruction(accounts: &impl ToAccountMetas, args: &impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+/// PDA DAMM v2 uses to emit CPI events.
+pub fn event_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &ID).0
+}
+
+pub mod accounts {
+    use super::*;
+
+    pub struct CreatePosition {
+        pub owner: Pubkey,
+        pub position_nft_mint: Pubkey,
+        pub position_nft_account: Pubkey,
+        pub pool: Pubkey,
+        pub position: Pubkey,
+        pub payer: Pubkey,
+        pub token_program: Pubkey,
+        pub event_authority: Pubkey,
+    }
+
+    impl ToAccountMetas for CreatePosition {
+        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+            vec![
+                AccountMeta::new_readonly(self.owner, false),
+                AccountMeta::new(self.position_nft_mint, true),
+                AccountMeta::new(self.position_nft_account, false),
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.position, false),
+                AccountMeta::new_readonly(POOL_AUTHORITY, false),
+                AccountMeta::new(self.payer, true),
+                AccountMeta::new_readonly(self.token_program, false),
+                AccountMeta::new_readonly(anchor_lang::system_program::ID, false),
+                AccountMeta::new_readonly(self.event_authority, false),
+                AccountMeta::new_readonly(ID, false),
+            ]
+        }
+    }
+
+    /// Accounts of `claim_position_fee`, `remove_liquidity` and
+    /// `remove_all_liquidity`. The claim only reads the pool.
+    pub struct ModifyPosition {
+        pub pool: Pubkey,
+        pub position: Pubkey,
+        pub token_a_account: Pubkey,
+        pub token_b_account: Pubkey,
+        pub token_a_vault: Pubkey,
+        pub token_b_vault: Pubkey,
+        pub token_a_mint: Pubkey,
+        pub token_b_mint: Pubkey,
+        pub position_nft_account: Pubkey,
+        pub owner: Pubkey,
+        pub token_a_program: Pubkey,
+        pub token_b_program: Pubkey,
+        pub event_authority: Pubkey,
+    }
+
+    impl ModifyPosition {
+        fn metas(&self, pool_writable: bool) -> Vec<AccountMeta> {
+            vec![
+                AccountMeta::new_readonly(POOL_AUTHORITY, false),
+                if pool_writable {
+                    AccountMeta::new(self.pool, false)
+                } else {
+                    AccountMeta::new_readonly(self.pool, false)
+                },
+                AccountMeta::new(self.position, false),
+                AccountMeta::new(self.token_a_account, false),
+                AccountMeta::new(self.token_b_account, false),
+                AccountMeta::new(self.token_a_vault, false),
+                AccountMeta::new(self.token_b_vault, false),
+                AccountMeta::new_readonly(self.token_a_mint, false),
+                AccountMeta::new_readonly(self.token_b_mint, false),
+                AccountMeta::new_readonly(self.position_nft_account, false),
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new_readonly(self.token_a_program, false),
+                AccountMeta::new_readonly(self.token_b_program, false),
+                AccountMeta::new_readonly(self.event_authority, false),
+                AccountMeta::new_readonly(ID, false),
+            ]
+        }
+    }
+
+    pub struct ClaimPositionFee(pub ModifyPosition);
+
+    impl ToAccountMetas for ClaimPositionFee {
+        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+            self.0.metas(false)
+        }
+    }
+
+    pub struct RemoveLiquidity(pub ModifyPosition);
+
+    impl ToAccountMetas for RemoveLiquidity {
+        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+            self.0.metas(true)
+        }
+    }
+
+    /// Same accounts as `remove_liquidity` without the pool authority.
+    pub struct AddLiquidity(pub ModifyPosition);
+
+    impl ToAccountMetas for AddLiquidity {
+        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+            self.0.metas(true).split_off(1)
+        }
+    }
+
+    pub struct PermanentLockPosition {
+        pub pool: Pubkey,
+        pub position: Pubkey,
+        pub position_nft_account: Pubkey,
+        pub owner: Pubkey,
+        pub event_authority: Pubkey,
+    }
+
+    impl ToAccountMetas for PermanentLockPosition {
+        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+            vec![
+                AccountMeta::new(self.pool, false),
+                AccountMeta::new(self.position, false),
+                AccountMeta::new_readonly(self.position_nft_account, false),
+                AccountMeta::new_readonly(self.owner, true),
+                AccountMeta::new_readonly(self.event_authority, false),
+                AccountMeta::new_readonly(ID, false),
+            ]
+        }
+    }
+}
+
+pub mod instruction {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    macro_rules! discriminator {
+        ($name:ident, $bytes:expr) => {
+            impl Discriminator for $name {
+                const DISCRIMINATOR: &'static [u8] = &$bytes;
+            }
+
+            impl InstructionData for $name {}
+        };
+    }
+
+    /// Opens an empty position; liquidity is deposited with `AddLiquidity`.
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct CreatePosition {}
+    discriminator!(CreatePosition, [48, 215, 197, 153, 96, 203, 180, 133]);
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct ClaimPositionFee {}
+    discriminator!(ClaimPositionFee, [180, 38, 154, 17, 133, 33, 162, 211]);
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct AddLiquidity {
+        pub liquidity_delta: u128,
+        pub token_a_amount_threshold: u64,
+        pub token_b_amount_threshold: u64,
+    }
+    discriminator!(AddLiquidity, [181, 157, 89, 67, 143, 182, 52, 72]);
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct RemoveLiquidity {
+        pub liquidity_delta: u128,
+        pub token_a_amount_threshold: u64,
+        pub token_b_amount_threshold: u64,
+    }
+    discriminator!(RemoveLiquidity, [80, 85, 209, 72, 24, 206, 177, 108]);
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct RemoveAllLiquidity {
+        pub token_a_amount_threshold: u64,
+        pub token_b_amount_threshold: u64,
+    }
+    discriminator!(RemoveAllLiquidity, [10, 51, 61, 35, 112, 105, 24, 85]);
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct PermanentLockPosition {
+        pub permanent_lock_liquidity: u128,
+    }
+    discriminator!(
+        PermanentLockPosition,
+        [165, 176, 125, 6, 231, 171, 186, 213]
+    );
+}
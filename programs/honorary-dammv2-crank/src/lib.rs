@@ -1,7 +1,30 @@
+// `#[program]` emits its IDL instructions, which call the deprecated
+// `AccountInfo::realloc`, in a generated module at the crate root, so no
+// narrower item can carry this allow.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::instruction::Instruction;
 use anchor_lang::solana_program::program::invoke_signed;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program::{self, Allocate, Assign};
+use anchor_spl::associated_token::{self, AssociatedToken, Create};
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    transfer_hook, BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::native_mint;
+use anchor_spl::token_2022::spl_token_2022::onchain;
+use anchor_spl::token_2022::spl_token_2022::state::{
+    Account as TokenAccountState, AccountState, Mint as MintState,
+};
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::{
+    self, CloseAccount, InitializeAccount3, Mint, TokenAccount, TokenInterface,
+};
+
+pub mod damm_v2;
+pub mod streamflow;
 
 declare_id!("ddcEKSibupo9XMaeHH66rVkpqCpWybXtAZWaBbMbF3h");
 
@@ -17,105 +40,299 @@ pub const STREAMFLOW_PROGRAM_ID: Pubkey = pubkey!("strmRqUCoQUgGUan5YhzUZa6Kqdzw
 #[constant]
 pub const POOL_AUTHORITY: Pubkey = pubkey!("HLnpSz9h2S4hiLQ43rnSD9XkcUThA7B8hQMKmDaiTLcC");
 
+#[constant]
+pub const MAX_CREATOR_SPLIT_RECIPIENTS: usize = 5;
+
+#[constant]
+pub const MAX_OPERATOR_FEE_BPS: u16 = 1000;
+
 #[program]
 pub mod honorary_dammv2_crank {
     use super::*;
 
     pub fn validate_pool(ctx: Context<ValidatePool>) -> Result<()> {
-        let pool = &ctx.accounts.pool;
-        require!(pool.collect_fee_mode == 1, ErrorCode::InvalidPoolConfig);
-        require!(
-            pool.token_mint_b == ctx.accounts.quote_mint.key(),
-            ErrorCode::InvalidQuoteMint
-        );
+        let pool = load_pool(&ctx.accounts.pool)?;
+        validate_quote_only_pool(&pool, &ctx.accounts.quote_mint.key(), &Clock::get()?)?;
         msg!("Pool validated for quote-only fee collection");
         Ok(())
     }
 
+    pub fn estimate_fees(ctx: Context<EstimateFees>) -> Result<FeeEstimate> {
+        let pool = load_pool(&ctx.accounts.pool)?;
+        let position = load_position(&ctx.accounts.position)?;
+        require_keys_eq!(
+            position.pool,
+            ctx.accounts.pool.key(),
+            ErrorCode::InvalidPosition
+        );
+        let (pending_base_fees, pending_quote_fees) = position.pending_fees(&pool);
+        Ok(FeeEstimate {
+            pending_base_fees,
+            pending_quote_fees,
+            carry_over: ctx.accounts.progress.carry_over,
+        })
+    }
+
     pub fn initialize_policy(
         ctx: Context<InitializePolicy>,
         y0: u64,
         investor_fee_share_bps: u16,
         daily_cap: Option<u64>,
         min_payout_lamports: u64,
+        creator_ata: Option<Pubkey>,
     ) -> Result<()> {
+        load_pool(&ctx.accounts.pool)?;
+
         let policy = &mut ctx.accounts.policy;
         policy.vault = ctx.accounts.vault.key();
+        policy.authority = ctx.accounts.payer.key();
+        policy.pool = ctx.accounts.pool.key();
+        policy.quote_mint = ctx.accounts.quote_mint.key();
+        policy.base_treasury = ctx.accounts.base_treasury.key();
+        policy.quote_treasury = ctx.accounts.quote_treasury.key();
+        policy.creator = ctx.accounts.creator.key();
+        policy.creator_ata = creator_ata;
         policy.y0 = y0;
         policy.investor_fee_share_bps = investor_fee_share_bps;
         policy.daily_cap = daily_cap;
         policy.min_payout_lamports = min_payout_lamports;
         emit!(PolicyInitialized {
             vault: policy.vault,
+            pool: policy.pool,
+            quote_mint: policy.quote_mint,
+            creator: policy.creator,
             y0,
             investor_fee_share_bps,
         });
         Ok(())
     }
 
-    pub fn initialize_honorary_position(
-        ctx: Context<InitializeHonoraryPosition>,
-        tick_lower_index: i32,
-        tick_upper_index: i32,
-        liquidity: u128,
+    pub fn set_creator(
+        ctx: Context<SetCreator>,
+        creator: Pubkey,
+        creator_ata: Option<Pubkey>,
     ) -> Result<()> {
-        let pool = &ctx.accounts.pool;
-        let current_tick_index = pool.tick_current;
+        let policy = &mut ctx.accounts.policy;
+        policy.creator = creator;
+        policy.creator_ata = creator_ata;
+        emit!(CreatorUpdated {
+            vault: policy.vault,
+            creator,
+            creator_ata,
+        });
+        Ok(())
+    }
 
+    pub fn set_creator_split(
+        ctx: Context<SetCreatorSplit>,
+        recipients: Vec<SplitRecipient>,
+        primary_index: u8,
+    ) -> Result<()> {
         require!(
-            tick_upper_index < current_tick_index,
-            ErrorCode::InvalidTickRange
+            recipients.len() <= MAX_CREATOR_SPLIT_RECIPIENTS,
+            ErrorCode::InvalidCreatorSplit
         );
+        if !recipients.is_empty() {
+            let total_bps: u32 = recipients.iter().map(|r| r.bps as u32).sum();
+            require!(total_bps == 10000, ErrorCode::InvalidCreatorSplit);
+            require!(
+                (primary_index as usize) < recipients.len(),
+                ErrorCode::InvalidCreatorSplit
+            );
+        }
+        // Recipient token accounts follow in order, so a recipient that could
+        // not receive the quote mint cannot stall every final page.
         require!(
-            tick_lower_index < tick_upper_index,
-            ErrorCode::InvalidTickRange
+            ctx.remaining_accounts.len() == recipients.len(),
+            ErrorCode::InvalidCreatorSplit
         );
+        for (recipient, recipient_ai) in recipients.iter().zip(ctx.remaining_accounts) {
+            require!(
+                recipient_ai.key() == recipient.token_account
+                    && is_deliverable(
+                        recipient_ai,
+                        &ctx.accounts.quote_mint.key(),
+                        &ctx.accounts.token_program.key(),
+                    )?,
+                ErrorCode::InvalidCreatorSplit
+            );
+        }
+        ctx.accounts.policy.creator_split_enabled = !recipients.is_empty();
+
+        let split = &mut ctx.accounts.creator_split;
+        split.vault = ctx.accounts.vault.key();
+        split.primary_index = primary_index;
+        split.recipient_count = recipients.len() as u8;
+        split.recipients = [SplitRecipient::default(); MAX_CREATOR_SPLIT_RECIPIENTS];
+        split.recipients[..recipients.len()].copy_from_slice(&recipients);
+
+        emit!(CreatorSplitUpdated {
+            vault: split.vault,
+            recipients,
+            primary_index,
+        });
+        Ok(())
+    }
 
-        let vault_key = ctx.accounts.vault.key();
-        let seeds = &[b"investor_fee_pos_owner", vault_key.as_ref()];
-        let signer_seeds = &[&seeds[..]];
-
-        let discriminator = [48, 215, 197, 153, 96, 203, 180, 133];
-        let mut ix_data = discriminator.to_vec();
-        ix_data.extend_from_slice(&tick_lower_index.to_le_bytes());
-        ix_data.extend_from_slice(&tick_upper_index.to_le_bytes());
-        ix_data.extend_from_slice(&liquidity.to_le_bytes());
-
-        let ix = Instruction {
-            program_id: DAMM_V2_PROGRAM_ID,
-            accounts: vec![
-                AccountMeta::new_readonly(ctx.accounts.owner_pda.key(), false),
-                AccountMeta::new(ctx.accounts.position_nft_mint.key(), false),
-                AccountMeta::new(ctx.accounts.position_nft_account.key(), false),
-                AccountMeta::new(ctx.accounts.pool.key(), false),
-                AccountMeta::new(ctx.accounts.position.key(), false),
-                AccountMeta::new_readonly(POOL_AUTHORITY, false),
-                AccountMeta::new(ctx.accounts.payer.key(), true),
-                AccountMeta::new_readonly(TOKEN22_PROGRAM_ID, false),
-                AccountMeta::new_readonly(anchor_lang::system_program::ID, false),
-                AccountMeta::new_readonly(ctx.accounts.event_authority.key(), false),
-                AccountMeta::new_readonly(DAMM_V2_PROGRAM_ID, false),
-            ],
-            data: ix_data,
-        };
+    pub fn set_operator_fee(
+        ctx: Context<SetOperatorFee>,
+        operator_fee_bps: u16,
+        operator_fee_recipient: Pubkey,
+        operator_fee_cap: Option<u64>,
+    ) -> Result<()> {
+        require!(
+            operator_fee_bps <= MAX_OPERATOR_FEE_BPS,
+            ErrorCode::OperatorFeeTooHigh
+        );
+        let policy = &mut ctx.accounts.policy;
+        policy.operator_fee_bps = operator_fee_bps;
+        policy.operator_fee_recipient = operator_fee_recipient;
+        policy.operator_fee_cap = operator_fee_cap;
+        emit!(OperatorFeeUpdated {
+            vault: policy.vault,
+            operator_fee_bps,
+            operator_fee_recipient,
+            operator_fee_cap,
+        });
+        Ok(())
+    }
 
-        invoke_signed(
-            &ix,
-            &[
-                ctx.accounts.owner_pda.to_account_info(),
-                ctx.accounts.position_nft_mint.to_account_info(),
-                ctx.accounts.position_nft_account.to_account_info(),
-                ctx.accounts.pool.to_account_info(),
-                ctx.accounts.position.to_account_info(),
-                ctx.accounts.pool_authority.to_account_info(),
-                ctx.accounts.payer.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-                ctx.accounts.event_authority.to_account_info(),
-                ctx.accounts.damm_program.to_account_info(),
-            ],
-            signer_seeds,
+    pub fn set_cranker_reward(
+        ctx: Context<SetCrankerReward>,
+        cranker_reward_fixed: u64,
+        cranker_reward_bps: u16,
+        cranker_reward_cap: u64,
+    ) -> Result<()> {
+        require!(
+            cranker_reward_fixed == 0 || cranker_reward_bps == 0,
+            ErrorCode::InvalidCrankerReward
+        );
+        require!(cranker_reward_bps <= 10000, ErrorCode::InvalidCrankerReward);
+        let policy = &mut ctx.accounts.policy;
+        policy.cranker_reward_fixed = cranker_reward_fixed;
+        policy.cranker_reward_bps = cranker_reward_bps;
+        policy.cranker_reward_cap = cranker_reward_cap;
+        emit!(CrankerRewardUpdated {
+            vault: policy.vault,
+            cranker_reward_fixed,
+            cranker_reward_bps,
+            cranker_reward_cap,
+        });
+        Ok(())
+    }
+
+    pub fn set_transfer_hook_accounts(
+        ctx: Context<SetTransferHookAccounts>,
+        transfer_hook_accounts: u8,
+    ) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        policy.transfer_hook_accounts = transfer_hook_accounts;
+        emit!(TransferHookAccountsUpdated {
+            vault: policy.vault,
+            transfer_hook_accounts,
+        });
+        Ok(())
+    }
+
+    pub fn set_native_payouts(ctx: Context<SetNativePayouts>, native_payouts: bool) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(
+            !native_payouts || !policy.auto_create_atas,
+            ErrorCode::InvalidPayoutMode
+        );
+        require!(
+            !native_payouts
+                || policy.quote_mint == anchor_spl::token::spl_token::native_mint::ID
+                || policy.quote_mint == native_mint::ID,
+            ErrorCode::QuoteMintNotNative
+        );
+        policy.native_payouts = native_payouts;
+        emit!(NativePayoutsUpdated {
+            vault: policy.vault,
+            native_payouts,
+        });
+        Ok(())
+    }
+
+    pub fn set_liquidity_accounts(ctx: Context<SetLiquidityAccounts>) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        policy.liquidity_token_a = ctx.accounts.token_a_account.key();
+        policy.liquidity_token_b = ctx.accounts.token_b_account.key();
+        emit!(LiquidityAccountsUpdated {
+            vault: policy.vault,
+            token_a_account: policy.liquidity_token_a,
+            token_b_account: policy.liquidity_token_b,
+        });
+        Ok(())
+    }
+
+    pub fn set_distribution_thresholds(
+        ctx: Context<SetDistributionThresholds>,
+        min_claim_amount: u64,
+        min_distribution_amount: u64,
+    ) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        policy.min_claim_amount = min_claim_amount;
+        policy.min_distribution_amount = min_distribution_amount;
+        emit!(DistributionThresholdsUpdated {
+            vault: policy.vault,
+            min_claim_amount,
+            min_distribution_amount,
+        });
+        Ok(())
+    }
+
+    pub fn set_ata_creation(
+        ctx: Context<SetAtaCreation>,
+        auto_create_atas: bool,
+        ata_reimbursement: u64,
+        ata_reimbursement_cap: u64,
+    ) -> Result<()> {
+        let policy = &mut ctx.accounts.policy;
+        require!(
+            !auto_create_atas || !policy.native_payouts,
+            ErrorCode::InvalidPayoutMode
+        );
+        policy.auto_create_atas = auto_create_atas;
+        policy.ata_reimbursement = ata_reimbursement;
+        policy.ata_reimbursement_cap = ata_reimbursement_cap;
+        emit!(AtaCreationUpdated {
+            vault: policy.vault,
+            auto_create_atas,
+            ata_reimbursement,
+            ata_reimbursement_cap,
+        });
+        Ok(())
+    }
+
+    pub fn initialize_honorary_position(ctx: Context<InitializeHonoraryPosition>) -> Result<()> {
+        let pool = load_pool(&ctx.accounts.pool)?;
+        validate_quote_only_pool(&pool, &ctx.accounts.policy.quote_mint, &Clock::get()?)?;
+        require_keys_eq!(
+            ctx.accounts.policy.position,
+            Pubkey::default(),
+            ErrorCode::PositionAlreadyInitialized
+        );
+
+        let vault_key = ctx.accounts.vault.key();
+        let owner_bump = [ctx.bumps.owner_pda];
+        let seeds: &[&[u8]] = &[b"investor_fee_pos_owner", vault_key.as_ref(), &owner_bump];
+
+        create_position(
+            &ctx.accounts.owner_pda.to_account_info(),
+            &ctx.accounts.position_nft_mint,
+            &ctx.accounts.position_nft_account,
+            &ctx.accounts.pool.to_account_info(),
+            &ctx.accounts.position,
+            &ctx.accounts.pool_authority,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.event_authority,
+            &ctx.accounts.damm_program,
+            &[seeds],
         )?;
+        ctx.accounts.policy.position = ctx.accounts.position.key();
 
         emit!(HonoraryPositionInitialized {
             vault: ctx.accounts.vault.key(),
@@ -124,16 +341,228 @@ pub mod honorary_dammv2_crank {
         Ok(())
     }
 
+    pub fn permanent_lock_position(ctx: Context<PermanentLockPosition>) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        let owner_bump = [ctx.bumps.owner_pda];
+        let seeds: &[&[u8]] = &[b"investor_fee_pos_owner", vault_key.as_ref(), &owner_bump];
+
+        // The whole unlocked liquidity is locked so `locked_permanently`
+        // holds for the entire position.
+        let liquidity = load_position(&ctx.accounts.position)?.unlocked_liquidity;
+        if liquidity > 0 {
+            let ix = damm_v2::instruction(
+                &damm_v2::accounts::PermanentLockPosition {
+                    pool: ctx.accounts.pool.key(),
+                    position: ctx.accounts.position.key(),
+                    position_nft_account: ctx.accounts.position_nft_account.key(),
+                    owner: ctx.accounts.owner_pda.key(),
+                    event_authority: ctx.accounts.event_authority.key(),
+                },
+                &damm_v2::instruction::PermanentLockPosition {
+                    permanent_lock_liquidity: liquidity,
+                },
+            );
+
+            invoke_signed(
+                &ix,
+                &[
+                    ctx.accounts.pool.to_account_info(),
+                    ctx.accounts.position.to_account_info(),
+                    ctx.accounts.position_nft_account.to_account_info(),
+                    ctx.accounts.owner_pda.to_account_info(),
+                    ctx.accounts.event_authority.to_account_info(),
+                    ctx.accounts.damm_program.to_account_info(),
+                ],
+                &[seeds],
+            )?;
+        }
+
+        let policy = &mut ctx.accounts.policy;
+        policy.locked_permanently = true;
+        emit!(PositionPermanentlyLocked {
+            vault: vault_key,
+            position: policy.position,
+            liquidity,
+        });
+        Ok(())
+    }
+
+    pub fn add_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, ModifyLiquidity<'info>>,
+        liquidity_delta: u128,
+        token_a_amount_threshold: u64,
+        token_b_amount_threshold: u64,
+    ) -> Result<()> {
+        ctx.accounts.policy.require_unlocked()?;
+        ctx.accounts.check_pool_mints()?;
+        let clock = Clock::get()?;
+        let vault_key = ctx.accounts.vault.key();
+        let owner_bump = [ctx.bumps.owner_pda];
+        let seeds: &[&[u8]] = &[b"investor_fee_pos_owner", vault_key.as_ref(), &owner_bump];
+
+        let hook_len = ctx.accounts.policy.transfer_hook_accounts as usize;
+        require!(
+            ctx.remaining_accounts.len() >= hook_len,
+            ErrorCode::MissingTransferHookAccounts
+        );
+        let hook_accounts = &ctx.remaining_accounts[..hook_len];
+
+        // DAMM v2 pulls deposits from accounts owned by the position owner, so
+        // the maximum amounts are staged in the treasuries and the unused
+        // remainder is returned to the authority afterwards. The treasuries
+        // also hold investor funds, so each side is staged gross of its
+        // transfer fee and must not end up below its starting balance.
+        let pre_a = ctx.accounts.base_treasury.amount;
+        let pre_b = ctx.accounts.quote_treasury.amount;
+        let accounts = &ctx.accounts;
+        let base_fee = transfer_fee_config(&accounts.token_mint_a.to_account_info())?;
+        let quote_fee = transfer_fee_config(&accounts.quote_mint.to_account_info())?;
+        let stage_a = TreasuryTransfer {
+            token_program: accounts.base_token_program.to_account_info(),
+            from: accounts.token_a_account.to_account_info(),
+            mint: accounts.token_mint_a.to_account_info(),
+            decimals: accounts.token_mint_a.decimals,
+            authority: accounts.authority.to_account_info(),
+            signer_seeds: &[],
+            transfer_fee: base_fee,
+            epoch: clock.epoch,
+            hook_accounts: &[],
+        };
+        stage_a.transfer(
+            accounts.base_treasury.to_account_info(),
+            stage_a.gross_amount(token_a_amount_threshold)?,
+        )?;
+        let stage_b = TreasuryTransfer {
+            token_program: accounts.token_program.to_account_info(),
+            from: accounts.token_b_account.to_account_info(),
+            mint: accounts.quote_mint.to_account_info(),
+            decimals: accounts.quote_mint.decimals,
+            authority: accounts.authority.to_account_info(),
+            signer_seeds: &[],
+            transfer_fee: quote_fee,
+            epoch: clock.epoch,
+            hook_accounts,
+        };
+        stage_b.transfer(
+            accounts.quote_treasury.to_account_info(),
+            stage_b.gross_amount(token_b_amount_threshold)?,
+        )?;
+
+        accounts
+            .liquidity_cpi(
+                accounts.base_treasury.to_account_info(),
+                accounts.quote_treasury.to_account_info(),
+                &[seeds],
+            )
+            .add_liquidity(
+                liquidity_delta,
+                token_a_amount_threshold,
+                token_b_amount_threshold,
+            )?;
+
+        ctx.accounts.base_treasury.reload()?;
+        ctx.accounts.quote_treasury.reload()?;
+        let accounts = &ctx.accounts;
+        require!(
+            accounts.base_treasury.amount >= pre_a && accounts.quote_treasury.amount >= pre_b,
+            ErrorCode::TreasuryUnderfunded
+        );
+        let refund_a = accounts.base_treasury.amount - pre_a;
+        let refund_b = accounts.quote_treasury.amount - pre_b;
+        if refund_a > 0 {
+            TreasuryTransfer {
+                token_program: accounts.base_token_program.to_account_info(),
+                from: accounts.base_treasury.to_account_info(),
+                mint: accounts.token_mint_a.to_account_info(),
+                decimals: accounts.token_mint_a.decimals,
+                authority: accounts.owner_pda.to_account_info(),
+                signer_seeds: &[seeds],
+                transfer_fee: base_fee,
+                epoch: clock.epoch,
+                hook_accounts: &[],
+            }
+            .transfer(accounts.token_a_account.to_account_info(), refund_a)?;
+        }
+        if refund_b > 0 {
+            TreasuryTransfer {
+                token_program: accounts.token_program.to_account_info(),
+                from: accounts.quote_treasury.to_account_info(),
+                mint: accounts.quote_mint.to_account_info(),
+                decimals: accounts.quote_mint.decimals,
+                authority: accounts.owner_pda.to_account_info(),
+                signer_seeds: &[seeds],
+                transfer_fee: quote_fee,
+                epoch: clock.epoch,
+                hook_accounts,
+            }
+            .transfer(accounts.token_b_account.to_account_info(), refund_b)?;
+        }
+
+        emit!(LiquidityAdded {
+            vault: vault_key,
+            position: accounts.position.key(),
+            liquidity_delta,
+            token_a_amount: token_a_amount_threshold.saturating_sub(refund_a),
+            token_b_amount: token_b_amount_threshold.saturating_sub(refund_b),
+        });
+        Ok(())
+    }
+
+    pub fn remove_liquidity(
+        ctx: Context<ModifyLiquidity>,
+        liquidity_delta: u128,
+        token_a_amount_threshold: u64,
+        token_b_amount_threshold: u64,
+    ) -> Result<()> {
+        ctx.accounts.policy.require_unlocked()?;
+        ctx.accounts.check_pool_mints()?;
+        let vault_key = ctx.accounts.vault.key();
+        let owner_bump = [ctx.bumps.owner_pda];
+        let seeds: &[&[u8]] = &[b"investor_fee_pos_owner", vault_key.as_ref(), &owner_bump];
+
+        let pre_a = ctx.accounts.token_a_account.amount;
+        let pre_b = ctx.accounts.token_b_account.amount;
+        let accounts = &ctx.accounts;
+        accounts
+            .liquidity_cpi(
+                accounts.token_a_account.to_account_info(),
+                accounts.token_b_account.to_account_info(),
+                &[seeds],
+            )
+            .remove_liquidity(
+                liquidity_delta,
+                token_a_amount_threshold,
+                token_b_amount_threshold,
+            )?;
+
+        ctx.accounts.token_a_account.reload()?;
+        ctx.accounts.token_b_account.reload()?;
+        emit!(LiquidityRemoved {
+            vault: vault_key,
+            position: ctx.accounts.position.key(),
+            liquidity_delta,
+            token_a_amount: ctx.accounts.token_a_account.amount.saturating_sub(pre_a),
+            token_b_amount: ctx.accounts.token_b_account.amount.saturating_sub(pre_b),
+        });
+        Ok(())
+    }
+
     pub fn initialize_progress(ctx: Context<InitializeProgress>) -> Result<()> {
         let progress = &mut ctx.accounts.progress;
         progress.vault = ctx.accounts.vault.key();
         progress.last_distribution_ts = 0;
         progress.current_day_start_ts = 0;
         progress.claimed_for_day = 0;
+        progress.operator_fee_for_day = 0;
         progress.investor_intended_for_day = 0;
         progress.creator_share_for_day = 0;
         progress.actual_distributed = 0;
+        progress.cranker_rewards_for_day = 0;
+        progress.transfer_fees_for_day = 0;
+        progress.investor_transfer_fees_for_day = 0;
+        progress.ata_reimbursements_for_day = 0;
         progress.carry_over = 0;
+        progress.deferred_for_day = false;
         progress.cursor = 0;
 
         emit!(ProgressInitialized {
@@ -151,6 +580,7 @@ pub mod honorary_dammv2_crank {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_stream(
         ctx: Context<CreateStream>,
         start_time: u64,
@@ -172,9 +602,7 @@ pub mod honorary_dammv2_crank {
         pausable: bool,
         can_update_rate: bool,
     ) -> Result<()> {
-        let discriminator = [230, 9, 241, 173, 57, 159, 239, 202];
-        let mut ix_data = discriminator.to_vec();
-        let args = CreateUncheckedWithPayerArgs {
+        let args = streamflow::instruction::CreateUncheckedWithPayer {
             start_time,
             net_amount_deposited,
             period,
@@ -194,26 +622,20 @@ pub mod honorary_dammv2_crank {
             pausable,
             can_update_rate,
         };
-        ix_data.extend_from_slice(&args.try_to_vec()?);
-
-        let ix = Instruction {
-            program_id: STREAMFLOW_PROGRAM_ID,
-            accounts: vec![
-                AccountMeta::new(ctx.accounts.payer.key(), true),
-                AccountMeta::new(ctx.accounts.sender.key(), true),
-                AccountMeta::new(ctx.accounts.sender_tokens.key(), false),
-                AccountMeta::new(ctx.accounts.metadata.key(), false),
-                AccountMeta::new(ctx.accounts.escrow_tokens.key(), false),
-                AccountMeta::new(ctx.accounts.withdrawor.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.mint.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.fee_oracle.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.rent.key(), false),
-                AccountMeta::new_readonly(STREAMFLOW_PROGRAM_ID, false),
-                AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
-                AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
-            ],
-            data: ix_data,
-        };
+        let ix = streamflow::instruction(
+            &streamflow::accounts::CreateUncheckedWithPayer {
+                payer: ctx.accounts.payer.key(),
+                sender: ctx.accounts.sender.key(),
+                sender_tokens: ctx.accounts.sender_tokens.key(),
+                metadata: ctx.accounts.metadata.key(),
+                escrow_tokens: ctx.accounts.escrow_tokens.key(),
+                withdrawor: ctx.accounts.withdrawor.key(),
+                mint: ctx.accounts.mint.key(),
+                fee_oracle: ctx.accounts.fee_oracle.key(),
+                token_program: ctx.accounts.token_program.key(),
+            },
+            &args,
+        );
 
         invoke_signed(
             &ix,
@@ -248,6 +670,87 @@ pub mod honorary_dammv2_crank {
         let progress = &mut ctx.accounts.progress;
         let policy = &ctx.accounts.policy;
 
+        let pool = load_pool(&ctx.accounts.pool)?;
+        require_keys_eq!(
+            pool.token_a_mint,
+            ctx.accounts.token_mint_a.key(),
+            ErrorCode::InvalidPool
+        );
+        require_keys_eq!(
+            pool.token_b_mint,
+            ctx.accounts.quote_mint.key(),
+            ErrorCode::InvalidQuoteMint
+        );
+        let position = load_position(&ctx.accounts.position)?;
+        require_keys_eq!(
+            position.pool,
+            ctx.accounts.pool.key(),
+            ErrorCode::InvalidPosition
+        );
+        require_keys_eq!(
+            position.nft_mint,
+            ctx.accounts.position_nft_account.mint,
+            ErrorCode::InvalidPosition
+        );
+        let (_, pending_quote_fees) = position.pending_fees(&pool);
+
+        let vault_key = ctx.accounts.vault.key();
+        let owner_bump = [ctx.bumps.owner_pda];
+        let owner_seeds: &[&[u8]] = &[b"investor_fee_pos_owner", vault_key.as_ref(), &owner_bump];
+        let signer_seeds = &[owner_seeds];
+
+        let hook_len = policy.transfer_hook_accounts as usize;
+        require!(
+            ctx.remaining_accounts.len() >= hook_len,
+            ErrorCode::MissingTransferHookAccounts
+        );
+        let (hook_accounts, page_remaining_accounts) = ctx.remaining_accounts.split_at(hook_len);
+        if hook_len == 0 {
+            require!(
+                transfer_hook_program_id(&ctx.accounts.quote_mint.to_account_info())?.is_none(),
+                ErrorCode::MissingTransferHookAccounts
+            );
+        }
+
+        let treasury_transfer = TreasuryTransfer {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            from: ctx.accounts.treasury.to_account_info(),
+            mint: ctx.accounts.quote_mint.to_account_info(),
+            decimals: ctx.accounts.quote_mint.decimals,
+            authority: ctx.accounts.owner_pda.to_account_info(),
+            signer_seeds,
+            transfer_fee: transfer_fee_config(&ctx.accounts.quote_mint.to_account_info())?,
+            epoch: clock.epoch,
+            hook_accounts,
+        };
+        let unwrap_bump = [ctx.bumps.native_unwrap.unwrap_or_default()];
+        let unwrap_seeds: &[&[u8]] = &[b"native_unwrap", vault_key.as_ref(), &unwrap_bump];
+        let native_accounts = if policy.native_payouts {
+            Some(NativeAccounts {
+                unwrap_account: ctx
+                    .accounts
+                    .native_unwrap
+                    .as_ref()
+                    .ok_or(ErrorCode::MissingNativeAccounts)?
+                    .to_account_info(),
+                payer: ctx
+                    .accounts
+                    .cranker
+                    .as_ref()
+                    .ok_or(ErrorCode::MissingNativeAccounts)?
+                    .to_account_info(),
+                system_program: ctx
+                    .accounts
+                    .system_program
+                    .as_ref()
+                    .ok_or(ErrorCode::MissingNativeAccounts)?
+                    .to_account_info(),
+                unwrap_seeds,
+            })
+        } else {
+            None
+        };
+
         let needs_distribution_reset =
             progress.last_distribution_ts == 0 || now >= progress.last_distribution_ts + 86400;
 
@@ -255,78 +758,139 @@ pub mod honorary_dammv2_crank {
             progress.last_distribution_ts = now;
             progress.current_day_start_ts = now;
             progress.actual_distributed = 0;
+            progress.cranker_rewards_for_day = 0;
+            progress.transfer_fees_for_day = 0;
+            progress.investor_transfer_fees_for_day = 0;
+            progress.ata_reimbursements_for_day = 0;
+            progress.deferred_for_day = false;
             progress.cursor = 0;
 
-            let owner_pda_info = ctx.accounts.owner_pda.to_account_info();
-            let pool_authority_info = ctx.accounts.pool_authority.to_account_info();
-            let pool_info = ctx.accounts.pool.to_account_info();
-            let position_info = ctx.accounts.position.to_account_info();
-            let token_vault_a_info = ctx.accounts.token_vault_a.to_account_info();
-            let token_vault_b_info = ctx.accounts.token_vault_b.to_account_info();
-            let token_mint_a_info = ctx.accounts.token_mint_a.to_account_info();
-            let quote_mint_info = ctx.accounts.quote_mint.to_account_info();
-            let position_nft_account_info = ctx.accounts.position_nft_account.to_account_info();
-            let event_authority_info = ctx.accounts.event_authority.to_account_info();
-            let damm_program_info = ctx.accounts.damm_program.to_account_info();
-
-            let (fee_a, fee_b) = claim_fees(
-                &owner_pda_info,
-                &pool_authority_info,
-                &pool_info,
-                &position_info,
-                &mut ctx.accounts.base_treasury,
-                &mut ctx.accounts.treasury,
-                &token_vault_a_info,
-                &token_vault_b_info,
-                &token_mint_a_info,
-                &quote_mint_info,
-                &position_nft_account_info,
-                &event_authority_info,
-                &damm_program_info,
-                &ctx.accounts.token_program,
-                &ctx.accounts.vault.key(),
-            )?;
+            let skip_claim = policy.skips_claim(pending_quote_fees);
+            let mut fee_b = 0;
+            if !skip_claim {
+                let owner_pda_info = ctx.accounts.owner_pda.to_account_info();
+                let pool_authority_info = ctx.accounts.pool_authority.to_account_info();
+                let pool_info = ctx.accounts.pool.to_account_info();
+                let position_info = ctx.accounts.position.to_account_info();
+                let token_vault_a_info = ctx.accounts.token_vault_a.to_account_info();
+                let token_vault_b_info = ctx.accounts.token_vault_b.to_account_info();
+                let token_mint_a_info = ctx.accounts.token_mint_a.to_account_info();
+                let quote_mint_info = ctx.accounts.quote_mint.to_account_info();
+                let position_nft_account_info = ctx.accounts.position_nft_account.to_account_info();
+                let event_authority_info = ctx.accounts.event_authority.to_account_info();
+                let damm_program_info = ctx.accounts.damm_program.to_account_info();
+
+                let (fee_a, claimed) = claim_fees(
+                    &owner_pda_info,
+                    &pool_authority_info,
+                    &pool_info,
+                    &position_info,
+                    &mut ctx.accounts.base_treasury,
+                    &mut ctx.accounts.treasury,
+                    &token_vault_a_info,
+                    &token_vault_b_info,
+                    &token_mint_a_info,
+                    &quote_mint_info,
+                    &position_nft_account_info,
+                    &event_authority_info,
+                    &damm_program_info,
+                    &ctx.accounts.base_token_program,
+                    &ctx.accounts.token_program,
+                    signer_seeds,
+                )?;
 
-            require!(fee_a == 0, ErrorCode::BaseFeeDetected);
-            progress.claimed_for_day = fee_b;
+                require!(fee_a == 0, ErrorCode::BaseFeeDetected);
+                fee_b = claimed;
 
-            emit!(QuoteFeesClaimed {
-                vault: ctx.accounts.vault.key(),
-                amount: fee_b,
-            });
+                emit!(QuoteFeesClaimed {
+                    vault: ctx.accounts.vault.key(),
+                    amount: fee_b,
+                    transfer_fee_withheld: treasury_transfer.inbound_fee(fee_b),
+                });
+            }
+            progress.claimed_for_day = fee_b;
 
-            let total_available = progress.carry_over + progress.claimed_for_day;
-            let f_locked = if policy.y0 == 0 {
-                0
-            } else {
-                (locked_total * 10000) / policy.y0
-            };
-            let eligible_bps = policy.investor_fee_share_bps.min(f_locked as u16);
-            let mut investor_intended =
-                (total_available as u128 * eligible_bps as u128 / 10000) as u64;
-            if let Some(cap) = policy.daily_cap {
-                investor_intended = investor_intended.min(cap);
+            let operator_fee = policy.operator_fee(fee_b);
+            if operator_fee > 0 {
+                let operator_fee_account = ctx
+                    .accounts
+                    .operator_fee_account
+                    .as_ref()
+                    .ok_or(ErrorCode::MissingOperatorFeeAccount)?;
+                progress.transfer_fees_for_day += treasury_transfer
+                    .transfer(operator_fee_account.to_account_info(), operator_fee)?;
+
+                emit!(OperatorFeeCollected {
+                    vault: ctx.accounts.vault.key(),
+                    day_ts: progress.current_day_start_ts,
+                    claimed: fee_b,
+                    operator_fee_bps: policy.operator_fee_bps,
+                    amount: operator_fee,
+                    recipient: operator_fee_account.key(),
+                });
             }
+            progress.operator_fee_for_day = operator_fee;
+
+            let total_available =
+                progress.carry_over + progress.claimed_for_day - progress.operator_fee_for_day;
+            if policy.defers_distribution(skip_claim, total_available) {
+                progress.investor_intended_for_day = 0;
+                progress.creator_share_for_day = 0;
+                progress.carry_over = total_available;
+                progress.deferred_for_day = true;
+
+                emit!(DistributionDeferred {
+                    vault: ctx.accounts.vault.key(),
+                    day_ts: progress.current_day_start_ts,
+                    pending_quote_fees,
+                    claimed: fee_b,
+                    carry_over: total_available,
+                });
+                return Ok(());
+            }
+            let investor_intended = policy.investor_intended(total_available, locked_total);
             progress.investor_intended_for_day = investor_intended;
             progress.creator_share_for_day = total_available.saturating_sub(investor_intended);
             progress.carry_over = 0;
         }
 
+        require!(!progress.deferred_for_day, ErrorCode::DistributionDeferred);
         require!(page_index == progress.cursor, ErrorCode::InvalidPageIndex);
 
-        let page_accounts = ctx.remaining_accounts.chunks(2);
+        let split_len = match &ctx.accounts.creator_split {
+            Some(split) if is_final_page => split.recipient_count as usize,
+            None if is_final_page && policy.creator_split_enabled => {
+                return err!(ErrorCode::InvalidCreatorSplit);
+            }
+            _ => 0,
+        };
+        require!(
+            page_remaining_accounts.len() >= split_len,
+            ErrorCode::InvalidCreatorSplit
+        );
+        let (investor_accounts, split_accounts) =
+            page_remaining_accounts.split_at(page_remaining_accounts.len() - split_len);
+
+        let entry_len = policy.investor_entry_len();
+        let page_accounts = investor_accounts.chunks(entry_len);
         let mut page_distributed = 0u64;
+        let mut page_investor_fees = 0u64;
+        let mut page_fees_withheld = 0u64;
+        let mut native_payouts = Vec::new();
+        let mut atas_created = 0u64;
+        let mut entries_processed = 0usize;
+        let rent = Rent::get()?;
 
         for chunk in page_accounts {
-            if chunk.len() < 2 {
+            if chunk.len() < entry_len {
                 continue;
             }
+            entries_processed += 1;
 
             let stream_ai = &chunk[0];
             let investor_ata_ai = &chunk[1];
 
-            let stream_data = stream_ai.data.borrow();
-            let stream = Stream::try_deserialize_unchecked(&mut &stream_data[..])?;
+            let stream = Stream::load(stream_ai)?;
             let unlocked = stream.unlocked_amount(now);
             let locked_i = stream.deposited_amount.saturating_sub(unlocked);
 
@@ -334,65 +898,196 @@ pub mod honorary_dammv2_crank {
                 continue;
             }
 
-            let weight = if locked_total == 0 {
-                0
-            } else {
-                (locked_i as u128 * 1_000_000u128) / (locked_total as u128)
-            } as u64;
-            let payout =
-                (progress.investor_intended_for_day as u128 * weight as u128 / 1_000_000) as u64;
-
-            if payout >= policy.min_payout_lamports {
-                let vault_key = ctx.accounts.vault.key();
-                let seeds = &[b"investor_fee_pos_owner", vault_key.as_ref()];
-                let signer_seeds = &[&seeds[..]];
-
-                token::transfer(
-                    CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.treasury.to_account_info(),
-                            to: investor_ata_ai.clone(),
-                            authority: ctx.accounts.owner_pda.to_account_info(),
-                        },
-                        signer_seeds,
-                    ),
-                    payout,
-                )?;
-
-                page_distributed += payout;
-            } else {
-                progress.carry_over += payout;
+            let payout = investor_payout(
+                progress.investor_intended_for_day,
+                investor_weight(locked_i, locked_total),
+            );
+            // The investor receives the payout net of any transfer fee, now
+            // or when an escrowed payout is claimed.
+            let fee = treasury_transfer.fee(payout)?;
+
+            if payout - fee >= policy.min_payout_lamports {
+                if native_accounts.is_some() {
+                    require_keys_eq!(
+                        investor_ata_ai.key(),
+                        stream.recipient,
+                        ErrorCode::InvalidRecipient
+                    );
+                    // Skipped payouts stay in the treasury and reach the
+                    // carry-over through the final page's undistributed sum.
+                    if !can_receive_lamports(investor_ata_ai, payout, &rent) {
+                        continue;
+                    }
+                    native_payouts.push((investor_ata_ai.clone(), payout));
+                } else {
+                    if policy.auto_create_atas {
+                        let wallet_ai = &chunk[2];
+                        require_keys_eq!(
+                            wallet_ai.key(),
+                            stream.recipient,
+                            ErrorCode::InvalidRecipient
+                        );
+                        require_keys_eq!(
+                            investor_ata_ai.key(),
+                            associated_token::get_associated_token_address_with_program_id(
+                                &stream.recipient,
+                                &ctx.accounts.quote_mint.key(),
+                                &ctx.accounts.token_program.key(),
+                            ),
+                            ErrorCode::InvalidRecipient
+                        );
+                        if investor_ata_ai.data_is_empty() {
+                            let payer = ctx
+                                .accounts
+                                .cranker
+                                .as_ref()
+                                .ok_or(ErrorCode::MissingAtaCreationAccounts)?;
+                            let system_program = ctx
+                                .accounts
+                                .system_program
+                                .as_ref()
+                                .ok_or(ErrorCode::MissingAtaCreationAccounts)?;
+                            let associated_token_program = ctx
+                                .accounts
+                                .associated_token_program
+                                .as_ref()
+                                .ok_or(ErrorCode::MissingAtaCreationAccounts)?;
+                            associated_token::create_idempotent(CpiContext::new(
+                                associated_token_program.to_account_info(),
+                                Create {
+                                    payer: payer.to_account_info(),
+                                    associated_token: investor_ata_ai.clone(),
+                                    authority: wallet_ai.clone(),
+                                    mint: ctx.accounts.quote_mint.to_account_info(),
+                                    system_program: system_program.to_account_info(),
+                                    token_program: ctx.accounts.token_program.to_account_info(),
+                                },
+                            ))?;
+                            atas_created += 1;
+                        }
+                    }
+
+                    if is_deliverable(
+                        investor_ata_ai,
+                        &ctx.accounts.quote_mint.key(),
+                        &ctx.accounts.token_program.key(),
+                    )? {
+                        require_keys_eq!(
+                            token_account_owner(investor_ata_ai)?,
+                            stream.recipient,
+                            ErrorCode::InvalidRecipient
+                        );
+                        page_fees_withheld +=
+                            treasury_transfer.transfer(investor_ata_ai.clone(), payout)?;
+                    } else {
+                        let payer = ctx
+                            .accounts
+                            .cranker
+                            .as_ref()
+                            .ok_or(ErrorCode::MissingEscrowAccounts)?;
+                        let system_program = ctx
+                            .accounts
+                            .system_program
+                            .as_ref()
+                            .ok_or(ErrorCode::MissingEscrowAccounts)?;
+                        escrow_payout(
+                            &chunk[entry_len - 1],
+                            &payer.to_account_info(),
+                            &system_program.to_account_info(),
+                            &vault_key,
+                            stream_ai.key,
+                            stream.recipient,
+                            payout,
+                        )?;
+                    }
+                }
+
+                page_distributed += payout - fee;
+                page_investor_fees += fee;
             }
         }
 
+        if let Some(native) = &native_accounts {
+            treasury_transfer.transfer_native(native, &native_payouts)?;
+        }
+
         progress.actual_distributed += page_distributed;
+        progress.investor_transfer_fees_for_day += page_investor_fees;
+        progress.transfer_fees_for_day += page_fees_withheld;
         progress.cursor += 1;
 
+        let mut cranker_reward = 0;
+        if let Some(cranker_token_account) = &ctx.accounts.cranker_token_account {
+            if entries_processed > 0 || is_final_page {
+                cranker_reward = policy.cranker_reward(
+                    progress.claimed_for_day,
+                    progress.cranker_rewards_for_day,
+                    progress
+                        .creator_share_for_day
+                        .saturating_sub(progress.ata_reimbursements_for_day),
+                );
+            }
+            if cranker_reward > 0 {
+                progress.transfer_fees_for_day += treasury_transfer
+                    .transfer(cranker_token_account.to_account_info(), cranker_reward)?;
+                progress.cranker_rewards_for_day += cranker_reward;
+            }
+
+            let ata_reimbursement = policy.ata_reimbursement(
+                atas_created,
+                progress.ata_reimbursements_for_day,
+                progress
+                    .creator_share_for_day
+                    .saturating_sub(progress.cranker_rewards_for_day)
+                    .saturating_sub(progress.ata_reimbursements_for_day),
+            );
+            if ata_reimbursement > 0 {
+                progress.transfer_fees_for_day += treasury_transfer
+                    .transfer(cranker_token_account.to_account_info(), ata_reimbursement)?;
+                progress.ata_reimbursements_for_day += ata_reimbursement;
+            }
+        }
+
         if is_final_page {
-            let undistributed = progress
-                .investor_intended_for_day
-                .saturating_sub(progress.actual_distributed);
-            progress.carry_over += undistributed;
-
-            let total_to_creator = progress.creator_share_for_day;
-            if total_to_creator > 0 {
-                let vault_key = ctx.accounts.vault.key();
-                let seeds = &[b"investor_fee_pos_owner", vault_key.as_ref()];
-                let signer_seeds = &[&seeds[..]];
-                token::transfer(
-                    CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        Transfer {
-                            from: ctx.accounts.treasury.to_account_info(),
-                            to: ctx.accounts.creator_ata.to_account_info(),
-                            authority: ctx.accounts.owner_pda.to_account_info(),
-                        },
-                        signer_seeds,
-                    ),
-                    total_to_creator,
-                )?;
+            progress.carry_over_undistributed();
+
+            let mut total_to_creator = progress
+                .creator_share_for_day
+                .saturating_sub(progress.cranker_rewards_for_day)
+                .saturating_sub(progress.ata_reimbursements_for_day);
+            let mut creator_fees = 0u64;
+            if total_to_creator > 0 && split_len > 0 {
+                let split = ctx.accounts.creator_split.as_ref().unwrap();
+                let amounts = split.split_amount(total_to_creator);
+
+                for (i, recipient_ai) in split_accounts.iter().enumerate() {
+                    require!(
+                        recipient_ai.key() == split.recipients[i].token_account,
+                        ErrorCode::InvalidCreatorSplit
+                    );
+                    if amounts[i] == 0 {
+                        continue;
+                    }
+                    creator_fees += treasury_transfer.transfer(recipient_ai.clone(), amounts[i])?;
+                }
+            } else if let (true, Some(native)) = (total_to_creator > 0, &native_accounts) {
+                let creator = ctx
+                    .accounts
+                    .creator
+                    .as_ref()
+                    .ok_or(ErrorCode::MissingNativeAccounts)?
+                    .to_account_info();
+                if can_receive_lamports(&creator, total_to_creator, &rent) {
+                    treasury_transfer.transfer_native(native, &[(creator, total_to_creator)])?;
+                } else {
+                    progress.carry_over += total_to_creator;
+                    total_to_creator = 0;
+                }
+            } else if total_to_creator > 0 {
+                creator_fees = treasury_transfer
+                    .transfer(ctx.accounts.creator_ata.to_account_info(), total_to_creator)?;
             }
+            progress.transfer_fees_for_day += creator_fees;
 
             emit!(CreatorPayoutDayClosed {
                 vault: ctx.accounts.vault.key(),
@@ -400,8 +1095,9 @@ pub mod honorary_dammv2_crank {
                 claimed: progress.claimed_for_day,
                 investor_intended: progress.investor_intended_for_day,
                 actual_distributed: progress.actual_distributed,
-                creator_received: total_to_creator,
+                creator_received: total_to_creator - creator_fees,
                 carry_over: progress.carry_over,
+                transfer_fees_withheld: progress.transfer_fees_for_day,
             });
         }
 
@@ -409,111 +1105,847 @@ pub mod honorary_dammv2_crank {
             vault: ctx.accounts.vault.key(),
             page_index,
             amount: page_distributed,
+            cranker_reward,
+            transfer_fee_withheld: page_fees_withheld,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_unclaimed<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimUnclaimed<'info>>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let policy = &ctx.accounts.policy;
+        let unclaimed = &ctx.accounts.unclaimed;
+
+        let vault_key = ctx.accounts.vault.key();
+        let owner_bump = [ctx.bumps.owner_pda];
+        let owner_seeds: &[&[u8]] = &[b"investor_fee_pos_owner", vault_key.as_ref(), &owner_bump];
+
+        let hook_len = policy.transfer_hook_accounts as usize;
+        require!(
+            ctx.remaining_accounts.len() >= hook_len,
+            ErrorCode::MissingTransferHookAccounts
+        );
+
+        let treasury_transfer = TreasuryTransfer {
+            token_program: ctx.accounts.token_program.to_account_info(),
+            from: ctx.accounts.treasury.to_account_info(),
+            mint: ctx.accounts.quote_mint.to_account_info(),
+            decimals: ctx.accounts.quote_mint.decimals,
+            authority: ctx.accounts.owner_pda.to_account_info(),
+            signer_seeds: &[owner_seeds],
+            transfer_fee: transfer_fee_config(&ctx.accounts.quote_mint.to_account_info())?,
+            epoch: clock.epoch,
+            hook_accounts: &ctx.remaining_accounts[..hook_len],
+        };
+        let transfer_fee_withheld = treasury_transfer.transfer(
+            ctx.accounts.recipient_token_account.to_account_info(),
+            unclaimed.amount,
+        )?;
+
+        emit!(UnclaimedPayoutClaimed {
+            vault: vault_key,
+            stream: unclaimed.stream,
+            recipient: unclaimed.recipient,
+            amount: unclaimed.amount,
+            transfer_fee_withheld,
         });
 
         Ok(())
     }
 }
 
-fn claim_fees<'info>(
+/// Copies a DAMM v2 account out of `account`, checking its owner, size and
+/// discriminator.
+fn load_damm_account<T: bytemuck::Pod>(
+    account: &AccountInfo,
+    discriminator: [u8; 8],
+    error_code: ErrorCode,
+) -> Result<Box<T>> {
+    require_keys_eq!(*account.owner, DAMM_V2_PROGRAM_ID, error_code);
+    parse_damm_account(&account.try_borrow_data()?, discriminator, error_code)
+}
+
+/// Copies a DAMM v2 account out of raw account data, so off-chain callers can
+/// decode fetched accounts with the same layout checks.
+pub fn parse_damm_account<T: bytemuck::Pod>(
+    data: &[u8],
+    discriminator: [u8; 8],
+    error_code: ErrorCode,
+) -> Result<Box<T>> {
+    let len = std::mem::size_of::<T>();
+    if data.len() < 8 + len || data[..8] != discriminator {
+        return Err(error_code.into());
+    }
+    let mut state = bytemuck::zeroed_box::<T>();
+    bytemuck::bytes_of_mut(state.as_mut()).copy_from_slice(&data[8..8 + len]);
+    Ok(state)
+}
+
+fn load_pool(account: &AccountInfo) -> Result<Box<Pool>> {
+    load_damm_account(account, POOL_DISCRIMINATOR, ErrorCode::InvalidPool)
+}
+
+fn load_position(account: &AccountInfo) -> Result<Box<Position>> {
+    load_damm_account(account, POSITION_DISCRIMINATOR, ErrorCode::InvalidPosition)
+}
+
+/// Checks every pool condition the honorary position relies on to accrue
+/// fees in the quote mint only.
+fn validate_quote_only_pool(pool: &Pool, quote_mint: &Pubkey, clock: &Clock) -> Result<()> {
+    require!(
+        pool.collect_fee_mode == COLLECT_FEE_MODE_ONLY_B,
+        ErrorCode::InvalidPoolConfig
+    );
+    require_keys_eq!(pool.token_b_mint, *quote_mint, ErrorCode::InvalidQuoteMint);
+    require!(
+        pool.pool_status == POOL_STATUS_ENABLED,
+        ErrorCode::PoolDisabled
+    );
+    require!(
+        pool.activation_type == ACTIVATION_TYPE_SLOT
+            || pool.activation_type == ACTIVATION_TYPE_TIMESTAMP,
+        ErrorCode::InvalidActivationType
+    );
+    let now = if pool.activation_type == ACTIVATION_TYPE_SLOT {
+        clock.slot
+    } else {
+        clock.unix_timestamp.max(0) as u64
+    };
+    require!(now >= pool.activation_point, ErrorCode::PoolNotActivated);
+    require!(
+        pool.sqrt_min_price < pool.sqrt_max_price,
+        ErrorCode::InvalidPriceRange
+    );
+    require!(
+        pool.sqrt_min_price <= pool.sqrt_price && pool.sqrt_price <= pool.sqrt_max_price,
+        ErrorCode::PriceOutOfRange
+    );
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_position<'info>(
     owner_pda: &AccountInfo<'info>,
-    pool_authority: &AccountInfo<'info>,
+    position_nft_mint: &AccountInfo<'info>,
+    position_nft_account: &AccountInfo<'info>,
     pool: &AccountInfo<'info>,
     position: &AccountInfo<'info>,
-    base_treasury: &mut Account<'info, TokenAccount>,
-    treasury: &mut Account<'info, TokenAccount>,
-    token_vault_a: &AccountInfo<'info>,
-    token_vault_b: &AccountInfo<'info>,
-    token_mint_a: &AccountInfo<'info>,
-    quote_mint: &AccountInfo<'info>,
-    position_nft_account: &AccountInfo<'info>,
+    pool_authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
     event_authority: &AccountInfo<'info>,
     damm_program: &AccountInfo<'info>,
-    token_program: &Program<'info, Token>,
-    vault_key: &Pubkey,
-) -> Result<(u64, u64)> {
-    let seeds = &[b"investor_fee_pos_owner", vault_key.as_ref()];
-    let signer_seeds = &[&seeds[..]];
-
-    let discriminator = [180, 38, 154, 17, 133, 33, 162, 211];
-    let ix_data = discriminator.to_vec();
-
-    let pre_fee_a = base_treasury.amount;
-    let pre_fee_b = treasury.amount;
-
-    let ix = Instruction {
-        program_id: DAMM_V2_PROGRAM_ID,
-        accounts: vec![
-            AccountMeta::new_readonly(POOL_AUTHORITY, false),
-            AccountMeta::new_readonly(pool.key(), false),
-            AccountMeta::new(position.key(), false),
-            AccountMeta::new(base_treasury.key(), false),
-            AccountMeta::new(treasury.key(), false),
-            AccountMeta::new(token_vault_a.key(), false),
-            AccountMeta::new(token_vault_b.key(), false),
-            AccountMeta::new_readonly(token_mint_a.key(), false),
-            AccountMeta::new_readonly(quote_mint.key(), false),
-            AccountMeta::new_readonly(position_nft_account.key(), false),
-            AccountMeta::new_readonly(owner_pda.key(), false),
-            AccountMeta::new_readonly(TOKEN22_PROGRAM_ID, false),
-            AccountMeta::new_readonly(TOKEN22_PROGRAM_ID, false),
-            AccountMeta::new_readonly(event_authority.key(), false),
-            AccountMeta::new_readonly(DAMM_V2_PROGRAM_ID, false),
-        ],
-        data: ix_data,
-    };
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let ix = damm_v2::instruction(
+        &damm_v2::accounts::CreatePosition {
+            owner: owner_pda.key(),
+            position_nft_mint: position_nft_mint.key(),
+            position_nft_account: position_nft_account.key(),
+            pool: pool.key(),
+            position: position.key(),
+            payer: payer.key(),
+            token_program: TOKEN22_PROGRAM_ID,
+            event_authority: event_authority.key(),
+        },
+        &damm_v2::instruction::CreatePosition {},
+    );
 
     invoke_signed(
         &ix,
         &[
-            pool_authority.clone(),
+            owner_pda.clone(),
+            position_nft_mint.clone(),
+            position_nft_account.clone(),
             pool.clone(),
             position.clone(),
-            base_treasury.to_account_info(),
-            treasury.to_account_info(),
-            token_vault_a.clone(),
-            token_vault_b.clone(),
-            token_mint_a.clone(),
-            quote_mint.clone(),
-            position_nft_account.clone(),
-            owner_pda.clone(),
-            token_program.to_account_info(),
-            token_program.to_account_info(),
+            pool_authority.clone(),
+            payer.clone(),
+            token_program.clone(),
+            system_program.clone(),
             event_authority.clone(),
             damm_program.clone(),
         ],
         signer_seeds,
     )?;
 
-    base_treasury.reload()?;
-    treasury.reload()?;
-
-    let fee_a = base_treasury.amount - pre_fee_a;
-    let fee_b = treasury.amount - pre_fee_b;
+    Ok(())
+}
 
-    Ok((fee_a, fee_b))
+/// DAMM v2 liquidity instructions on the honorary position, signed by the
+/// owner PDA.
+struct LiquidityCpi<'a, 'info> {
+    owner_pda: AccountInfo<'info>,
+    pool_authority: AccountInfo<'info>,
+    pool: AccountInfo<'info>,
+    position: AccountInfo<'info>,
+    token_a_account: AccountInfo<'info>,
+    token_b_account: AccountInfo<'info>,
+    token_vault_a: AccountInfo<'info>,
+    token_vault_b: AccountInfo<'info>,
+    token_mint_a: AccountInfo<'info>,
+    token_mint_b: AccountInfo<'info>,
+    position_nft_account: AccountInfo<'info>,
+    token_a_program: AccountInfo<'info>,
+    token_b_program: AccountInfo<'info>,
+    event_authority: AccountInfo<'info>,
+    damm_program: AccountInfo<'info>,
+    signer_seeds: &'a [&'a [&'a [u8]]],
 }
 
-impl Stream {
-    fn unlocked_amount(&self, now: u64) -> u64 {
-        if now < self.start_time {
-            0
-        } else if now < self.start_time + self.cliff {
-            0
-        } else {
-            let elapsed_after_cliff = now - self.start_time - self.cliff;
-            let periods_elapsed = elapsed_after_cliff / self.period;
+impl LiquidityCpi<'_, '_> {
+    /// Deposits up to the thresholds from the token accounts, which must be
+    /// owned by the owner PDA.
+    fn add_liquidity(
+        &self,
+        liquidity_delta: u128,
+        token_a_amount_threshold: u64,
+        token_b_amount_threshold: u64,
+    ) -> Result<()> {
+        let ix = damm_v2::instruction(
+            &damm_v2::accounts::AddLiquidity(self.accounts()),
+            &damm_v2::instruction::AddLiquidity {
+                liquidity_delta,
+                token_a_amount_threshold,
+                token_b_amount_threshold,
+            },
+        );
+        self.invoke(&ix, false)
+    }
+
+    /// Withdraws at least the thresholds into the token accounts.
+    fn remove_liquidity(
+        &self,
+        liquidity_delta: u128,
+        token_a_amount_threshold: u64,
+        token_b_amount_threshold: u64,
+    ) -> Result<()> {
+        let ix = damm_v2::instruction(
+            &damm_v2::accounts::RemoveLiquidity(self.accounts()),
+            &damm_v2::instruction::RemoveLiquidity {
+                liquidity_delta,
+                token_a_amount_threshold,
+                token_b_amount_threshold,
+            },
+        );
+        self.invoke(&ix, true)
+    }
+
+    fn accounts(&self) -> damm_v2::accounts::ModifyPosition {
+        damm_v2::accounts::ModifyPosition {
+            pool: self.pool.key(),
+            position: self.position.key(),
+            token_a_account: self.token_a_account.key(),
+            token_b_account: self.token_b_account.key(),
+            token_a_vault: self.token_vault_a.key(),
+            token_b_vault: self.token_vault_b.key(),
+            token_a_mint: self.token_mint_a.key(),
+            token_b_mint: self.token_mint_b.key(),
+            position_nft_account: self.position_nft_account.key(),
+            owner: self.owner_pda.key(),
+            token_a_program: self.token_a_program.key(),
+            token_b_program: self.token_b_program.key(),
+            event_authority: self.event_authority.key(),
+        }
+    }
+
+    fn invoke(&self, ix: &Instruction, with_pool_authority: bool) -> Result<()> {
+        let mut account_infos = Vec::with_capacity(15);
+        if with_pool_authority {
+            account_infos.push(self.pool_authority.clone());
+        }
+        account_infos.extend([
+            self.pool.clone(),
+            self.position.clone(),
+            self.token_a_account.clone(),
+            self.token_b_account.clone(),
+            self.token_vault_a.clone(),
+            self.token_vault_b.clone(),
+            self.token_mint_a.clone(),
+            self.token_mint_b.clone(),
+            self.position_nft_account.clone(),
+            self.owner_pda.clone(),
+            self.token_a_program.clone(),
+            self.token_b_program.clone(),
+            self.event_authority.clone(),
+            self.damm_program.clone(),
+        ]);
+        invoke_signed(ix, &account_infos, self.signer_seeds)?;
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn claim_fees<'info>(
+    owner_pda: &AccountInfo<'info>,
+    pool_authority: &AccountInfo<'info>,
+    pool: &AccountInfo<'info>,
+    position: &AccountInfo<'info>,
+    base_treasury: &mut InterfaceAccount<'info, TokenAccount>,
+    treasury: &mut InterfaceAccount<'info, TokenAccount>,
+    token_vault_a: &AccountInfo<'info>,
+    token_vault_b: &AccountInfo<'info>,
+    token_mint_a: &AccountInfo<'info>,
+    quote_mint: &AccountInfo<'info>,
+    position_nft_account: &AccountInfo<'info>,
+    event_authority: &AccountInfo<'info>,
+    damm_program: &AccountInfo<'info>,
+    token_a_program: &Interface<'info, TokenInterface>,
+    token_b_program: &Interface<'info, TokenInterface>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<(u64, u64)> {
+    let pre_fee_a = base_treasury.amount;
+    let pre_fee_b = treasury.amount;
+
+    let ix = damm_v2::instruction(
+        &damm_v2::accounts::ClaimPositionFee(damm_v2::accounts::ModifyPosition {
+            pool: pool.key(),
+            position: position.key(),
+            token_a_account: base_treasury.key(),
+            token_b_account: treasury.key(),
+            token_a_vault: token_vault_a.key(),
+            token_b_vault: token_vault_b.key(),
+            token_a_mint: token_mint_a.key(),
+            token_b_mint: quote_mint.key(),
+            position_nft_account: position_nft_account.key(),
+            owner: owner_pda.key(),
+            token_a_program: token_a_program.key(),
+            token_b_program: token_b_program.key(),
+            event_authority: event_authority.key(),
+        }),
+        &damm_v2::instruction::ClaimPositionFee {},
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            pool_authority.clone(),
+            pool.clone(),
+            position.clone(),
+            base_treasury.to_account_info(),
+            treasury.to_account_info(),
+            token_vault_a.clone(),
+            token_vault_b.clone(),
+            token_mint_a.clone(),
+            quote_mint.clone(),
+            position_nft_account.clone(),
+            owner_pda.clone(),
+            token_a_program.to_account_info(),
+            token_b_program.to_account_info(),
+            event_authority.clone(),
+            damm_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+
+    base_treasury.reload()?;
+    treasury.reload()?;
+
+    let fee_a = base_treasury.amount - pre_fee_a;
+    let fee_b = treasury.amount - pre_fee_b;
+
+    Ok((fee_a, fee_b))
+}
+
+fn transfer_fee_config(mint: &AccountInfo) -> Result<Option<TransferFeeConfig>> {
+    if *mint.owner != Token2022::id() {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(mint_state
+        .get_extension::<TransferFeeConfig>()
+        .ok()
+        .copied())
+}
+
+fn transfer_hook_program_id(mint: &AccountInfo) -> Result<Option<Pubkey>> {
+    if *mint.owner != Token2022::id() {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(transfer_hook::get_program_id(&mint_state))
+}
+
+/// Whether `account` is an initialized, unfrozen token account for `mint`
+/// that a payout can be transferred into.
+fn is_deliverable(account: &AccountInfo, mint: &Pubkey, token_program: &Pubkey) -> Result<bool> {
+    if account.owner != token_program {
+        return Ok(false);
+    }
+    let data = account.try_borrow_data()?;
+    Ok(
+        match StateWithExtensions::<TokenAccountState>::unpack(&data) {
+            Ok(state) => state.base.mint == *mint && state.base.state == AccountState::Initialized,
+            Err(_) => false,
+        },
+    )
+}
+
+/// Owner of a token account that `is_deliverable` accepted.
+fn token_account_owner(account: &AccountInfo) -> Result<Pubkey> {
+    let data = account.try_borrow_data()?;
+    let state = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+    Ok(state.base.owner)
+}
+
+/// Whether `account` can be sent `amount` lamports. A wallet that does not
+/// exist yet cannot be funded below rent exemption.
+fn can_receive_lamports(account: &AccountInfo, amount: u64, rent: &Rent) -> bool {
+    account.lamports() > 0 || rent.is_exempt(amount, account.data_len())
+}
+
+/// Records `amount` owed to `recipient` in the `["unclaimed", vault, stream]`
+/// PDA, creating it on first use. The tokens stay in the treasury.
+fn escrow_payout<'info>(
+    unclaimed: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    vault: &Pubkey,
+    stream: &Pubkey,
+    recipient: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let (expected, bump) =
+        Pubkey::find_program_address(&[b"unclaimed", vault.as_ref(), stream.as_ref()], &crate::ID);
+    require_keys_eq!(unclaimed.key(), expected, ErrorCode::InvalidUnclaimedPayout);
+
+    if unclaimed.data_is_empty() {
+        create_pda_account(
+            unclaimed,
+            payer,
+            system_program,
+            8 + std::mem::size_of::<UnclaimedPayout>(),
+            &crate::ID,
+            &[b"unclaimed", vault.as_ref(), stream.as_ref(), &[bump]],
+        )?;
+        let payout = UnclaimedPayout {
+            vault: *vault,
+            stream: *stream,
+            recipient,
+            amount,
+            payer: payer.key(),
+        };
+        payout.try_serialize(&mut &mut unclaimed.try_borrow_mut_data()?[..])?;
+    } else {
+        require_keys_eq!(
+            *unclaimed.owner,
+            crate::ID,
+            ErrorCode::InvalidUnclaimedPayout
+        );
+        let mut data = unclaimed.try_borrow_mut_data()?;
+        let mut payout = UnclaimedPayout::try_deserialize(&mut &data[..])?;
+        // The escrow follows the stream, so a recipient transfer on the
+        // stream hands the whole balance to the new recipient.
+        payout.recipient = recipient;
+        payout.amount += amount;
+        payout.try_serialize(&mut &mut data[..])?;
+    }
+
+    emit!(InvestorPayoutEscrowed {
+        vault: *vault,
+        stream: *stream,
+        recipient,
+        amount,
+    });
+    Ok(())
+}
+
+/// Creates the PDA `account` with `space` bytes owned by `owner`, topping it
+/// up to rent exemption from `payer`. Unlike `create_account` this succeeds
+/// when lamports were already sent to the address. Returns what `payer` paid.
+fn create_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<u64> {
+    let top_up = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: account.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign {
+                account_to_assign: account.clone(),
+            },
+            &[seeds],
+        ),
+        owner,
+    )?;
+    Ok(top_up)
+}
+
+struct NativeAccounts<'a, 'info> {
+    unwrap_account: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    unwrap_seeds: &'a [&'a [u8]],
+}
+
+struct TreasuryTransfer<'a, 'info> {
+    token_program: AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    decimals: u8,
+    authority: AccountInfo<'info>,
+    signer_seeds: &'a [&'a [&'a [u8]]],
+    transfer_fee: Option<TransferFeeConfig>,
+    epoch: u64,
+    hook_accounts: &'a [AccountInfo<'info>],
+}
+
+impl<'info> TreasuryTransfer<'_, 'info> {
+    /// Fee withheld by a Token-2022 transfer-fee mint when the treasury
+    /// received `net_amount`.
+    fn inbound_fee(&self, net_amount: u64) -> u64 {
+        self.transfer_fee
+            .and_then(|config| config.calculate_inverse_epoch_fee(self.epoch, net_amount))
+            .unwrap_or(0)
+    }
+
+    /// Amount to send so that the treasury receives `net_amount` after the
+    /// transfer fee.
+    fn gross_amount(&self, net_amount: u64) -> Result<u64> {
+        Ok(net_amount
+            .checked_add(self.inbound_fee(net_amount))
+            .ok_or(ErrorCode::MathOverflow)?)
+    }
+
+    /// Fee a Token-2022 transfer-fee mint withholds from an outbound
+    /// transfer of `amount`.
+    fn fee(&self, amount: u64) -> Result<u64> {
+        match self.transfer_fee {
+            Some(config) => Ok(config
+                .calculate_epoch_fee(self.epoch, amount)
+                .ok_or(ErrorCode::MathOverflow)?),
+            None => Ok(0),
+        }
+    }
+
+    /// Transfers `amount` out of the treasury and returns the fee withheld
+    /// from it; the recipient receives `amount - fee`. Transfer-hook extra
+    /// accounts are resolved from `hook_accounts` when the mint has a hook.
+    fn transfer(&self, to: AccountInfo<'info>, amount: u64) -> Result<u64> {
+        if self.transfer_fee.is_some() {
+            let fee = self.fee(amount)?;
+            onchain::invoke_transfer_checked_with_fee(
+                self.token_program.key,
+                self.from.clone(),
+                self.mint.clone(),
+                to,
+                self.authority.clone(),
+                self.hook_accounts,
+                amount,
+                self.decimals,
+                fee,
+                self.signer_seeds,
+            )?;
+            return Ok(fee);
+        }
+
+        onchain::invoke_transfer_checked(
+            self.token_program.key,
+            self.from.clone(),
+            self.mint.clone(),
+            to,
+            self.authority.clone(),
+            self.hook_accounts,
+            amount,
+            self.decimals,
+            self.signer_seeds,
+        )?;
+        Ok(0)
+    }
+
+    /// Unwraps the sum of `payouts` from the WSOL treasury through a
+    /// temporary token account closed into the owner PDA, refunds its rent to
+    /// the payer and sends each recipient lamports.
+    fn transfer_native(
+        &self,
+        native: &NativeAccounts<'_, 'info>,
+        payouts: &[(AccountInfo<'info>, u64)],
+    ) -> Result<()> {
+        let total = payouts.iter().map(|(_, amount)| amount).sum::<u64>();
+        if total == 0 {
+            return Ok(());
+        }
+
+        let rent = create_pda_account(
+            &native.unwrap_account,
+            &native.payer,
+            &native.system_program,
+            TokenAccountState::LEN,
+            self.token_program.key,
+            native.unwrap_seeds,
+        )?;
+        token_interface::initialize_account3(CpiContext::new(
+            self.token_program.clone(),
+            InitializeAccount3 {
+                account: native.unwrap_account.clone(),
+                mint: self.mint.clone(),
+                authority: self.authority.clone(),
+            },
+        ))?;
+        self.transfer(native.unwrap_account.clone(), total)?;
+        token_interface::close_account(CpiContext::new_with_signer(
+            self.token_program.clone(),
+            CloseAccount {
+                account: native.unwrap_account.clone(),
+                destination: self.authority.clone(),
+                authority: self.authority.clone(),
+            },
+            self.signer_seeds,
+        ))?;
+
+        self.transfer_lamports(native, native.payer.clone(), rent)?;
+        for (to, amount) in payouts {
+            self.transfer_lamports(native, to.clone(), *amount)?;
+        }
+        Ok(())
+    }
+
+    fn transfer_lamports(
+        &self,
+        native: &NativeAccounts<'_, 'info>,
+        to: AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                native.system_program.clone(),
+                system_program::Transfer {
+                    from: self.authority.clone(),
+                    to,
+                },
+                self.signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
+
+impl Progress {
+    /// Carries the part of the investor share that no page paid out, such
+    /// as dust and skipped payouts, into the next day.
+    pub fn carry_over_undistributed(&mut self) -> u64 {
+        let undistributed = self
+            .investor_intended_for_day
+            .saturating_sub(self.actual_distributed)
+            .saturating_sub(self.investor_transfer_fees_for_day);
+        self.carry_over += undistributed;
+        undistributed
+    }
+}
+
+impl Policy {
+    pub fn skips_claim(&self, pending_quote_fees: u64) -> bool {
+        pending_quote_fees < self.min_claim_amount
+    }
+
+    pub fn defers_distribution(&self, claim_skipped: bool, total_available: u64) -> bool {
+        claim_skipped || total_available < self.min_distribution_amount
+    }
+
+    /// Share of Y0 still locked, in basis points, at most 10000.
+    pub fn f_locked_bps(&self, locked_total: u64) -> u64 {
+        (locked_total as u128 * 10000)
+            .checked_div(self.y0 as u128)
+            .unwrap_or(0)
+            .min(10000) as u64
+    }
+
+    /// Investor share of the day's fees: the configured share, scaled down
+    /// once less than that fraction of Y0 is still locked.
+    pub fn eligible_bps(&self, locked_total: u64) -> u16 {
+        self.investor_fee_share_bps
+            .min(self.f_locked_bps(locked_total) as u16)
+    }
+
+    /// Amount set aside for investors out of the day's `total_available`.
+    pub fn investor_intended(&self, total_available: u64, locked_total: u64) -> u64 {
+        let intended =
+            (total_available as u128 * self.eligible_bps(locked_total) as u128 / 10000) as u64;
+        match self.daily_cap {
+            Some(cap) => intended.min(cap),
+            None => intended,
+        }
+    }
+
+    /// Number of remaining accounts per investor in a crank page.
+    pub fn investor_entry_len(&self) -> usize {
+        if self.native_payouts {
+            2
+        } else if self.auto_create_atas {
+            4
+        } else {
+            3
+        }
+    }
+
+    /// Liquidity of a permanently locked position can no longer move.
+    fn require_unlocked(&self) -> Result<()> {
+        require!(
+            !self.locked_permanently,
+            ErrorCode::PositionPermanentlyLocked
+        );
+        Ok(())
+    }
+
+    fn ata_reimbursement(&self, created: u64, paid_today: u64, creator_remaining: u64) -> u64 {
+        self.ata_reimbursement
+            .saturating_mul(created)
+            .min(self.ata_reimbursement_cap.saturating_sub(paid_today))
+            .min(creator_remaining)
+    }
+
+    pub fn operator_fee(&self, claimed: u64) -> u64 {
+        let fee = (claimed as u128 * self.operator_fee_bps as u128 / 10000) as u64;
+        match self.operator_fee_cap {
+            Some(cap) => fee.min(cap),
+            None => fee,
+        }
+    }
+
+    fn cranker_reward(&self, claimed: u64, paid_today: u64, creator_share: u64) -> u64 {
+        let reward = if self.cranker_reward_bps > 0 {
+            (claimed as u128 * self.cranker_reward_bps as u128 / 10000) as u64
+        } else {
+            self.cranker_reward_fixed
+        };
+        let budget = self.cranker_reward_cap.min(creator_share);
+        reward.min(budget.saturating_sub(paid_today))
+    }
+}
+
+impl CreatorSplit {
+    fn split_amount(&self, total: u64) -> [u64; MAX_CREATOR_SPLIT_RECIPIENTS] {
+        let mut amounts = [0u64; MAX_CREATOR_SPLIT_RECIPIENTS];
+        let count = self.recipient_count as usize;
+        let mut assigned = 0u64;
+        for (amount, recipient) in amounts.iter_mut().zip(&self.recipients[..count]) {
+            *amount = (total as u128 * recipient.bps as u128 / 10000) as u64;
+            assigned += *amount;
+        }
+        amounts[self.primary_index as usize] += total - assigned;
+        amounts
+    }
+}
+
+impl Stream {
+    /// Reads a stream account, which must be owned by the Streamflow program.
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(
+            *account.owner,
+            STREAMFLOW_PROGRAM_ID,
+            ErrorCode::InvalidStream
+        );
+        Self::parse(&account.try_borrow_data()?)
+    }
+
+    /// Decodes raw Streamflow `Contract` data, so off-chain callers read
+    /// streams the same way `crank` does.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let contract = streamflow::state::Contract::deserialize(&mut &data[..])
+            .map_err(|_| ErrorCode::InvalidStream)?;
+        Ok(Self {
+            start_time: contract.ix.start_time,
+            deposited_amount: contract.ix.net_amount_deposited,
+            period: contract.ix.period,
+            amount_per_period: contract.ix.amount_per_period,
+            cliff: contract.ix.cliff.saturating_sub(contract.ix.start_time),
+            cliff_amount: contract.ix.cliff_amount,
+            withdrawn_amount: contract.amount_withdrawn,
+            recipient: contract.recipient,
+        })
+    }
+
+    #[allow(clippy::if_same_then_else)]
+    pub fn unlocked_amount(&self, now: u64) -> u64 {
+        if now < self.start_time {
+            0
+        } else if now < self.start_time + self.cliff {
+            0
+        } else {
+            let elapsed_after_cliff = now - self.start_time - self.cliff;
+            let periods_elapsed = elapsed_after_cliff / self.period;
             let unlocked = self.cliff_amount + (periods_elapsed * self.amount_per_period);
             unlocked.min(self.deposited_amount - self.withdrawn_amount)
         }
     }
 }
 
+/// Millionths of `locked_total` a stream with `locked` still locked holds.
+pub fn investor_weight(locked: u64, locked_total: u64) -> u64 {
+    (locked as u128 * 1_000_000)
+        .checked_div(locked_total as u128)
+        .unwrap_or(0) as u64
+}
+
+/// Pro-rata share of `investor_intended` for an investor `weight`.
+pub fn investor_payout(investor_intended: u64, weight: u64) -> u64 {
+    (investor_intended as u128 * weight as u128 / 1_000_000) as u64
+}
+
 #[derive(Accounts)]
 pub struct ValidatePool<'info> {
+    /// CHECK: DAMM v2 pool, parsed by `load_pool`
+    #[account(owner = DAMM_V2_PROGRAM_ID)]
+    pub pool: UncheckedAccount<'info>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct EstimateFees<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = pool @ ErrorCode::InvalidPool,
+        has_one = position @ ErrorCode::InvalidPosition
+    )]
+    pub policy: Account<'info, Policy>,
+    #[account(
+        seeds = [b"progress", vault.key().as_ref()],
+        bump
+    )]
+    pub progress: Account<'info, Progress>,
+    /// CHECK: DAMM v2 pool, parsed by `load_pool`
+    #[account(owner = DAMM_V2_PROGRAM_ID)]
+    pub pool: UncheckedAccount<'info>,
+    /// CHECK: DAMM v2 position, parsed by `load_position`
     #[account(owner = DAMM_V2_PROGRAM_ID)]
-    pub pool: Account<'info, Pool>,
-    pub quote_mint: Account<'info, Mint>,
+    pub position: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -526,13 +1958,201 @@ pub struct InitializePolicy<'info> {
         bump
     )]
     pub policy: Account<'info, Policy>,
+    /// Vault identifier; its signature keeps anyone else from claiming the
+    /// vault's policy.
+    pub vault: Signer<'info>,
+    #[account(
+        seeds = [b"investor_fee_pos_owner", vault.key().as_ref()],
+        bump
+    )]
+    pub owner_pda: SystemAccount<'info>,
+    /// CHECK: DAMM v2 pool, parsed by `load_pool` and re-validated against
+    /// quote_mint on every crank
+    #[account(owner = DAMM_V2_PROGRAM_ID @ ErrorCode::InvalidPool)]
+    pub pool: UncheckedAccount<'info>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    #[account(constraint = base_treasury.owner == owner_pda.key() @ ErrorCode::InvalidTreasury)]
+    pub base_treasury: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        constraint = quote_treasury.owner == owner_pda.key() @ ErrorCode::InvalidTreasury,
+        constraint = quote_treasury.mint == quote_mint.key() @ ErrorCode::InvalidQuoteMint
+    )]
+    pub quote_treasury: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: creator wallet, only its key is stored
+    pub creator: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCreator<'info> {
     /// CHECK: vault identifier
     pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub policy: Account<'info, Policy>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCreatorSplit<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        has_one = quote_mint @ ErrorCode::InvalidQuoteMint
+    )]
+    pub policy: Account<'info, Policy>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + std::mem::size_of::<CreatorSplit>(),
+        seeds = [b"creator_split", vault.key().as_ref()],
+        bump
+    )]
+    pub creator_split: Account<'info, CreatorSplit>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    #[account(constraint = token_program.key() == *quote_mint.to_account_info().owner @ ErrorCode::InvalidTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SetOperatorFee<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub policy: Account<'info, Policy>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCrankerReward<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub policy: Account<'info, Policy>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTransferHookAccounts<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub policy: Account<'info, Policy>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetNativePayouts<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub policy: Account<'info, Policy>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimUnclaimed<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        seeds = [b"investor_fee_pos_owner", vault.key().as_ref()],
+        bump
+    )]
+    pub owner_pda: SystemAccount<'info>,
+    #[account(
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = quote_mint @ ErrorCode::InvalidQuoteMint
+    )]
+    pub policy: Account<'info, Policy>,
+    #[account(
+        mut,
+        seeds = [b"unclaimed", vault.key().as_ref(), unclaimed.stream.as_ref()],
+        bump,
+        has_one = recipient @ ErrorCode::Unauthorized,
+        has_one = payer @ ErrorCode::InvalidUnclaimedPayout,
+        close = payer
+    )]
+    pub unclaimed: Account<'info, UnclaimedPayout>,
+    pub recipient: Signer<'info>,
+    #[account(mut)]
+    pub payer: SystemAccount<'info>,
+    #[account(
+        mut,
+        address = policy.quote_treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == quote_mint.key() @ ErrorCode::InvalidQuoteMint
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    #[account(constraint = token_program.key() == *quote_mint.to_account_info().owner @ ErrorCode::InvalidTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetDistributionThresholds<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub policy: Account<'info, Policy>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAtaCreation<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized
+    )]
+    pub policy: Account<'info, Policy>,
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct InitializeHonoraryPosition<'info> {
     /// CHECK: vault identifier
@@ -542,14 +2162,22 @@ pub struct InitializeHonoraryPosition<'info> {
         bump
     )]
     pub owner_pda: SystemAccount<'info>,
-    /// CHECK: validated by DAMM program
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = pool @ ErrorCode::InvalidPool
+    )]
+    pub policy: Account<'info, Policy>,
+    /// Fresh keypair; DAMM v2 initializes it as the position NFT mint.
     #[account(mut)]
-    pub position_nft_mint: UncheckedAccount<'info>,
+    pub position_nft_mint: Signer<'info>,
     /// CHECK: validated by DAMM program
     #[account(mut)]
     pub position_nft_account: UncheckedAccount<'info>,
+    /// CHECK: DAMM v2 pool, parsed by `load_pool`
     #[account(mut, owner = DAMM_V2_PROGRAM_ID)]
-    pub pool: Account<'info, Pool>,
+    pub pool: UncheckedAccount<'info>,
     /// CHECK: validated by DAMM program
     #[account(mut)]
     pub position: UncheckedAccount<'info>,
@@ -558,8 +2186,7 @@ pub struct InitializeHonoraryPosition<'info> {
     pub pool_authority: UncheckedAccount<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
-    #[account(address = TOKEN22_PROGRAM_ID)]
-    pub token_program: Program<'info, Token>,
+    pub token_program: Program<'info, Token2022>,
     pub system_program: Program<'info, System>,
     /// CHECK: event authority
     pub event_authority: UncheckedAccount<'info>,
@@ -568,6 +2195,168 @@ pub struct InitializeHonoraryPosition<'info> {
     pub damm_program: UncheckedAccount<'info>,
 }
 
+#[derive(Accounts)]
+pub struct PermanentLockPosition<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        seeds = [b"investor_fee_pos_owner", vault.key().as_ref()],
+        bump
+    )]
+    pub owner_pda: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        has_one = pool @ ErrorCode::InvalidPool,
+        has_one = position @ ErrorCode::InvalidPosition
+    )]
+    pub policy: Account<'info, Policy>,
+    pub authority: Signer<'info>,
+    /// CHECK: validated by DAMM program
+    #[account(mut, owner = DAMM_V2_PROGRAM_ID)]
+    pub pool: UncheckedAccount<'info>,
+    /// CHECK: registered position, validated by DAMM program
+    #[account(mut, owner = DAMM_V2_PROGRAM_ID)]
+    pub position: UncheckedAccount<'info>,
+    #[account(constraint = position_nft_account.owner == owner_pda.key() @ ErrorCode::InvalidPosition)]
+    pub position_nft_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: event authority
+    pub event_authority: UncheckedAccount<'info>,
+    /// CHECK: address constraint
+    #[account(address = DAMM_V2_PROGRAM_ID)]
+    pub damm_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetLiquidityAccounts<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        has_one = base_treasury @ ErrorCode::InvalidTreasury,
+        has_one = quote_mint @ ErrorCode::InvalidQuoteMint
+    )]
+    pub policy: Account<'info, Policy>,
+    pub authority: Signer<'info>,
+    pub base_treasury: InterfaceAccount<'info, TokenAccount>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        constraint = token_a_account.owner == authority.key() @ ErrorCode::InvalidLiquidityAccount,
+        constraint = token_a_account.mint == base_treasury.mint @ ErrorCode::InvalidLiquidityAccount
+    )]
+    pub token_a_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        constraint = token_b_account.owner == authority.key() @ ErrorCode::InvalidLiquidityAccount,
+        constraint = token_b_account.mint == quote_mint.key() @ ErrorCode::InvalidLiquidityAccount
+    )]
+    pub token_b_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyLiquidity<'info> {
+    /// CHECK: vault identifier
+    pub vault: AccountInfo<'info>,
+    #[account(
+        seeds = [b"investor_fee_pos_owner", vault.key().as_ref()],
+        bump
+    )]
+    pub owner_pda: SystemAccount<'info>,
+    #[account(
+        seeds = [b"policy", vault.key().as_ref()],
+        bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+        has_one = pool @ ErrorCode::InvalidPool,
+        has_one = position @ ErrorCode::InvalidPosition,
+        has_one = quote_mint @ ErrorCode::InvalidQuoteMint,
+        has_one = base_treasury @ ErrorCode::InvalidTreasury,
+        has_one = quote_treasury @ ErrorCode::InvalidTreasury
+    )]
+    pub policy: Box<Account<'info, Policy>>,
+    pub authority: Signer<'info>,
+    /// CHECK: DAMM v2 pool, parsed by `load_pool`
+    #[account(mut, owner = DAMM_V2_PROGRAM_ID)]
+    pub pool: UncheckedAccount<'info>,
+    /// CHECK: registered position, validated by DAMM program
+    #[account(mut, owner = DAMM_V2_PROGRAM_ID)]
+    pub position: UncheckedAccount<'info>,
+    #[account(constraint = position_nft_account.owner == owner_pda.key() @ ErrorCode::InvalidPosition)]
+    pub position_nft_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub base_treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub quote_treasury: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = policy.liquidity_token_a @ ErrorCode::InvalidLiquidityAccount)]
+    pub token_a_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, address = policy.liquidity_token_b @ ErrorCode::InvalidLiquidityAccount)]
+    pub token_b_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub token_vault_a: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub token_vault_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_mint_a: Box<InterfaceAccount<'info, Mint>>,
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(constraint = base_token_program.key() == *token_mint_a.to_account_info().owner @ ErrorCode::InvalidTokenProgram)]
+    pub base_token_program: Interface<'info, TokenInterface>,
+    #[account(constraint = token_program.key() == *quote_mint.to_account_info().owner @ ErrorCode::InvalidTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
+    /// CHECK: address constraint
+    #[account(address = POOL_AUTHORITY)]
+    pub pool_authority: UncheckedAccount<'info>,
+    /// CHECK: event authority
+    pub event_authority: UncheckedAccount<'info>,
+    /// CHECK: address constraint
+    #[account(address = DAMM_V2_PROGRAM_ID)]
+    pub damm_program: UncheckedAccount<'info>,
+}
+
+impl<'info> ModifyLiquidity<'info> {
+    fn check_pool_mints(&self) -> Result<()> {
+        let pool = load_pool(&self.pool)?;
+        require_keys_eq!(
+            pool.token_a_mint,
+            self.token_mint_a.key(),
+            ErrorCode::InvalidPool
+        );
+        require_keys_eq!(
+            pool.token_b_mint,
+            self.quote_mint.key(),
+            ErrorCode::InvalidQuoteMint
+        );
+        Ok(())
+    }
+
+    fn liquidity_cpi<'a>(
+        &self,
+        token_a_account: AccountInfo<'info>,
+        token_b_account: AccountInfo<'info>,
+        signer_seeds: &'a [&'a [&'a [u8]]],
+    ) -> LiquidityCpi<'a, 'info> {
+        LiquidityCpi {
+            owner_pda: self.owner_pda.to_account_info(),
+            pool_authority: self.pool_authority.to_account_info(),
+            pool: self.pool.to_account_info(),
+            position: self.position.to_account_info(),
+            token_a_account,
+            token_b_account,
+            token_vault_a: self.token_vault_a.to_account_info(),
+            token_vault_b: self.token_vault_b.to_account_info(),
+            token_mint_a: self.token_mint_a.to_account_info(),
+            token_mint_b: self.quote_mint.to_account_info(),
+            position_nft_account: self.position_nft_account.to_account_info(),
+            token_a_program: self.base_token_program.to_account_info(),
+            token_b_program: self.token_program.to_account_info(),
+            event_authority: self.event_authority.to_account_info(),
+            damm_program: self.damm_program.to_account_info(),
+            signer_seeds,
+        }
+    }
+}
+
 #[derive(Accounts)]
 pub struct InitializeProgress<'info> {
     /// CHECK: vault identifier
@@ -594,25 +2383,25 @@ pub struct InitializeTreasuryAccounts<'info> {
         bump
     )]
     pub owner_pda: SystemAccount<'info>,
-    pub token_mint_a: Account<'info, Mint>,
-    pub quote_mint: Account<'info, Mint>,
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
         constraint = base_treasury.owner == owner_pda.key(),
         constraint = base_treasury.mint == token_mint_a.key()
     )]
-    pub base_treasury: Account<'info, TokenAccount>,
+    pub base_treasury: InterfaceAccount<'info, TokenAccount>,
     #[account(
         mut,
         constraint = quote_treasury.owner == owner_pda.key(),
         constraint = quote_treasury.mint == quote_mint.key()
     )]
-    pub quote_treasury: Account<'info, TokenAccount>,
+    pub quote_treasury: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
-    #[account(address = TOKEN22_PROGRAM_ID)]
-    pub token_program: Program<'info, Token>,
+    #[account(constraint = token_program.key() == *quote_mint.to_account_info().owner @ ErrorCode::InvalidTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
@@ -620,6 +2409,7 @@ pub struct Crank<'info> {
     /// CHECK: vault identifier
     pub vault: AccountInfo<'info>,
     #[account(
+        mut,
         seeds = [b"investor_fee_pos_owner", vault.key().as_ref()],
         bump
     )]
@@ -632,36 +2422,89 @@ pub struct Crank<'info> {
     pub progress: Account<'info, Progress>,
     #[account(
         seeds = [b"policy", vault.key().as_ref()],
-        bump
+        bump,
+        has_one = pool @ ErrorCode::InvalidPool,
+        has_one = position @ ErrorCode::InvalidPosition,
+        has_one = quote_mint @ ErrorCode::InvalidQuoteMint,
+        has_one = base_treasury @ ErrorCode::InvalidTreasury
     )]
     pub policy: Account<'info, Policy>,
-    #[account(mut)]
-    pub base_treasury: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub treasury: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub creator_ata: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = base_treasury.owner == owner_pda.key() @ ErrorCode::InvalidTreasury,
+        constraint = base_treasury.mint == token_mint_a.key() @ ErrorCode::InvalidTreasury
+    )]
+    pub base_treasury: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = policy.quote_treasury @ ErrorCode::InvalidTreasury,
+        constraint = treasury.owner == owner_pda.key() @ ErrorCode::InvalidTreasury,
+        constraint = treasury.mint == quote_mint.key() @ ErrorCode::InvalidQuoteMint
+    )]
+    pub treasury: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = creator_ata.owner == policy.creator @ ErrorCode::InvalidCreatorAccount,
+        constraint = policy.creator_ata.is_none_or(|ata| ata == creator_ata.key()) @ ErrorCode::InvalidCreatorAccount,
+        constraint = creator_ata.mint == quote_mint.key() @ ErrorCode::InvalidQuoteMint
+    )]
+    pub creator_ata: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: DAMM v2 position, parsed by `load_position`
     #[account(owner = DAMM_V2_PROGRAM_ID)]
-    pub position: Account<'info, Position>,
-    pub token_mint_a: Account<'info, Mint>,
-    pub quote_mint: Account<'info, Mint>,
-    #[account(address = TOKEN22_PROGRAM_ID)]
-    pub token_program: Program<'info, Token>,
+    pub position: UncheckedAccount<'info>,
+    pub token_mint_a: InterfaceAccount<'info, Mint>,
+    pub quote_mint: InterfaceAccount<'info, Mint>,
+    #[account(constraint = base_token_program.key() == *token_mint_a.to_account_info().owner @ ErrorCode::InvalidTokenProgram)]
+    pub base_token_program: Interface<'info, TokenInterface>,
+    #[account(constraint = token_program.key() == *quote_mint.to_account_info().owner @ ErrorCode::InvalidTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
     #[account(mut)]
-    pub token_vault_a: Account<'info, TokenAccount>,
+    pub token_vault_a: InterfaceAccount<'info, TokenAccount>,
     #[account(mut)]
-    pub token_vault_b: Account<'info, TokenAccount>,
+    pub token_vault_b: InterfaceAccount<'info, TokenAccount>,
     /// CHECK: address constraint
     #[account(address = POOL_AUTHORITY)]
     pub pool_authority: UncheckedAccount<'info>,
-    pub pool: Account<'info, Pool>,
+    /// CHECK: DAMM v2 pool, parsed by `load_pool`
+    #[account(owner = DAMM_V2_PROGRAM_ID)]
+    pub pool: UncheckedAccount<'info>,
     #[account(mut)]
-    pub position_nft_account: Account<'info, TokenAccount>,
+    pub position_nft_account: InterfaceAccount<'info, TokenAccount>,
     /// CHECK: event authority
     pub event_authority: UncheckedAccount<'info>,
     /// CHECK: address constraint
     #[account(address = DAMM_V2_PROGRAM_ID)]
     pub damm_program: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"creator_split", vault.key().as_ref()],
+        bump
+    )]
+    pub creator_split: Option<Account<'info, CreatorSplit>>,
+    #[account(
+        mut,
+        address = policy.operator_fee_recipient @ ErrorCode::MissingOperatorFeeAccount,
+        constraint = operator_fee_account.mint == quote_mint.key() @ ErrorCode::InvalidQuoteMint
+    )]
+    pub operator_fee_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        mut,
+        constraint = cranker_token_account.mint == quote_mint.key() @ ErrorCode::InvalidQuoteMint
+    )]
+    pub cranker_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub cranker: Option<Signer<'info>>,
+    /// CHECK: temporary WSOL account created and closed within the instruction
+    #[account(
+        mut,
+        seeds = [b"native_unwrap", vault.key().as_ref()],
+        bump
+    )]
+    pub native_unwrap: Option<UncheckedAccount<'info>>,
+    /// CHECK: address constraint
+    #[account(mut, address = policy.creator @ ErrorCode::InvalidCreatorAccount)]
+    pub creator: Option<UncheckedAccount<'info>>,
+    pub system_program: Option<Program<'info, System>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
 }
 
 #[derive(Accounts)]
@@ -671,23 +2514,24 @@ pub struct CreateStream<'info> {
     #[account(mut)]
     pub sender: Signer<'info>,
     #[account(mut)]
-    pub sender_tokens: Account<'info, TokenAccount>,
+    pub sender_tokens: InterfaceAccount<'info, TokenAccount>,
     /// CHECK: validated by Streamflow
     #[account(mut)]
     pub metadata: UncheckedAccount<'info>,
     #[account(mut)]
-    pub escrow_tokens: Account<'info, TokenAccount>,
+    pub escrow_tokens: InterfaceAccount<'info, TokenAccount>,
     /// CHECK: validated by Streamflow
     #[account(mut)]
     pub withdrawor: UncheckedAccount<'info>,
-    pub mint: Account<'info, Mint>,
+    pub mint: InterfaceAccount<'info, Mint>,
     /// CHECK: fee oracle
     pub fee_oracle: UncheckedAccount<'info>,
     pub rent: Sysvar<'info, Rent>,
     /// CHECK: address constraint
     #[account(address = STREAMFLOW_PROGRAM_ID)]
     pub timelock_program: UncheckedAccount<'info>,
-    pub token_program: Program<'info, Token>,
+    #[account(constraint = token_program.key() == *mint.to_account_info().owner @ ErrorCode::InvalidTokenProgram)]
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
@@ -695,44 +2539,311 @@ pub struct CreateStream<'info> {
 #[derive(Default)]
 pub struct Policy {
     pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub pool: Pubkey,
+    pub position: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_treasury: Pubkey,
+    pub quote_treasury: Pubkey,
+    pub creator: Pubkey,
+    pub creator_ata: Option<Pubkey>,
     pub y0: u64,
     pub investor_fee_share_bps: u16,
     pub daily_cap: Option<u64>,
     pub min_payout_lamports: u64,
+    pub operator_fee_bps: u16,
+    pub operator_fee_recipient: Pubkey,
+    pub operator_fee_cap: Option<u64>,
+    pub cranker_reward_fixed: u64,
+    pub cranker_reward_bps: u16,
+    pub cranker_reward_cap: u64,
+    pub transfer_hook_accounts: u8,
+    pub native_payouts: bool,
+    pub auto_create_atas: bool,
+    pub ata_reimbursement: u64,
+    pub ata_reimbursement_cap: u64,
+    pub locked_permanently: bool,
+    pub liquidity_token_a: Pubkey,
+    pub liquidity_token_b: Pubkey,
+    pub min_claim_amount: u64,
+    pub min_distribution_amount: u64,
+    pub creator_split_enabled: bool,
+}
+
+#[account]
+#[derive(Default)]
+pub struct Progress {
+    pub vault: Pubkey,
+    pub last_distribution_ts: u64,
+    pub current_day_start_ts: u64,
+    pub claimed_for_day: u64,
+    pub operator_fee_for_day: u64,
+    pub investor_intended_for_day: u64,
+    pub creator_share_for_day: u64,
+    pub actual_distributed: u64,
+    pub cranker_rewards_for_day: u64,
+    pub transfer_fees_for_day: u64,
+    pub investor_transfer_fees_for_day: u64,
+    pub ata_reimbursements_for_day: u64,
+    pub carry_over: u64,
+    pub deferred_for_day: bool,
+    pub cursor: u16,
+}
+
+#[account]
+#[derive(Default)]
+pub struct UnclaimedPayout {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// Cranker that paid the rent, refunded when the payout is claimed.
+    pub payer: Pubkey,
+}
+
+#[account]
+#[derive(Default)]
+pub struct CreatorSplit {
+    pub vault: Pubkey,
+    pub primary_index: u8,
+    pub recipient_count: u8,
+    pub recipients: [SplitRecipient; MAX_CREATOR_SPLIT_RECIPIENTS],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct SplitRecipient {
+    pub token_account: Pubkey,
+    pub bps: u16,
+}
+
+/// Discriminator of the DAMM v2 `Pool` account.
+pub const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+/// `Pool.collect_fee_mode` value for pools that only collect fees in token B.
+pub const COLLECT_FEE_MODE_ONLY_B: u8 = 1;
+pub const POOL_STATUS_ENABLED: u8 = 0;
+pub const ACTIVATION_TYPE_SLOT: u8 = 0;
+pub const ACTIVATION_TYPE_TIMESTAMP: u8 = 1;
+
+/// Layout of the DAMM v2 `Pool` account, following its 8-byte discriminator.
+#[zero_copy]
+pub struct Pool {
+    pub pool_fees: PoolFeesStruct,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_vault: Pubkey,
+    pub token_b_vault: Pubkey,
+    pub whitelisted_vault: Pubkey,
+    pub partner: Pubkey,
+    pub liquidity: u128,
+    pub _padding: u128,
+    pub protocol_a_fee: u64,
+    pub protocol_b_fee: u64,
+    pub partner_a_fee: u64,
+    pub partner_b_fee: u64,
+    pub sqrt_min_price: u128,
+    pub sqrt_max_price: u128,
+    pub sqrt_price: u128,
+    pub activation_point: u64,
+    pub activation_type: u8,
+    pub pool_status: u8,
+    pub token_a_flag: u8,
+    pub token_b_flag: u8,
+    pub collect_fee_mode: u8,
+    pub pool_type: u8,
+    pub _padding_0: [u8; 2],
+    pub fee_a_per_liquidity: [u8; 32],
+    pub fee_b_per_liquidity: [u8; 32],
+    pub permanent_lock_liquidity: u128,
+    pub metrics: PoolMetrics,
+    pub creator: Pubkey,
+    pub _padding_1: [u64; 6],
+    pub reward_infos: [RewardInfo; 2],
+}
+
+#[zero_copy]
+pub struct PoolFeesStruct {
+    pub base_fee: BaseFeeStruct,
+    pub protocol_fee_percent: u8,
+    pub partner_fee_percent: u8,
+    pub referral_fee_percent: u8,
+    pub padding_0: [u8; 5],
+    pub dynamic_fee: DynamicFeeStruct,
+    pub padding_1: [u64; 2],
+}
+
+#[zero_copy]
+pub struct BaseFeeStruct {
+    pub cliff_fee_numerator: u64,
+    pub fee_scheduler_mode: u8,
+    pub padding_0: [u8; 5],
+    pub number_of_period: u16,
+    pub period_frequency: u64,
+    pub reduction_factor: u64,
+    pub padding_1: u64,
+}
+
+#[zero_copy]
+pub struct DynamicFeeStruct {
+    pub initialized: u8,
+    pub padding: [u8; 7],
+    pub max_volatility_accumulator: u32,
+    pub variable_fee_control: u32,
+    pub bin_step: u16,
+    pub filter_period: u16,
+    pub decay_period: u16,
+    pub reduction_factor: u16,
+    pub last_update_timestamp: u64,
+    pub bin_step_u128: u128,
+    pub sqrt_price_reference: u128,
+    pub volatility_accumulator: u128,
+    pub volatility_reference: u128,
+}
+
+#[zero_copy]
+pub struct PoolMetrics {
+    pub total_lp_a_fee: u128,
+    pub total_lp_b_fee: u128,
+    pub total_protocol_a_fee: u64,
+    pub total_protocol_b_fee: u64,
+    pub total_partner_a_fee: u64,
+    pub total_partner_b_fee: u64,
+    pub total_position: u64,
+    pub padding: u64,
+}
+
+#[zero_copy]
+pub struct RewardInfo {
+    pub initialized: u8,
+    pub reward_token_flag: u8,
+    pub _padding_0: [u8; 6],
+    pub _padding_1: [u8; 8],
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub funder: Pubkey,
+    pub reward_duration: u64,
+    pub reward_duration_end: u64,
+    pub reward_rate: u128,
+    pub reward_per_token_stored: [u8; 32],
+    pub last_update_time: u64,
+    pub cumulative_seconds_with_empty_liquidity_reward: u64,
+}
+
+const _: () = assert!(std::mem::size_of::<Pool>() == 1104);
+
+/// Discriminator of the DAMM v2 `Position` account.
+pub const POSITION_DISCRIMINATOR: [u8; 8] = [170, 188, 143, 228, 122, 64, 247, 208];
+
+/// Layout of the DAMM v2 `Position` account, following its 8-byte
+/// discriminator.
+#[zero_copy]
+pub struct Position {
+    pub pool: Pubkey,
+    pub nft_mint: Pubkey,
+    pub fee_a_per_token_checkpoint: [u8; 32],
+    pub fee_b_per_token_checkpoint: [u8; 32],
+    pub fee_a_pending: u64,
+    pub fee_b_pending: u64,
+    pub unlocked_liquidity: u128,
+    pub vested_liquidity: u128,
+    pub permanent_locked_liquidity: u128,
+    pub metrics: PositionMetrics,
+    pub reward_infos: [UserRewardInfo; 2],
+    pub padding: [u128; 6],
+}
+
+#[zero_copy]
+pub struct PositionMetrics {
+    pub total_claimed_a_fee: u64,
+    pub total_claimed_b_fee: u64,
+}
+
+#[zero_copy]
+pub struct UserRewardInfo {
+    pub reward_per_token_checkpoint: [u8; 32],
+    pub reward_pendings: u64,
+    pub total_claimed_rewards: u64,
 }
 
-#[account]
-#[derive(Default)]
-pub struct Progress {
-    pub vault: Pubkey,
-    pub last_distribution_ts: u64,
-    pub current_day_start_ts: u64,
-    pub claimed_for_day: u64,
-    pub investor_intended_for_day: u64,
-    pub creator_share_for_day: u64,
-    pub actual_distributed: u64,
-    pub carry_over: u64,
-    pub cursor: u16,
+const _: () = assert!(std::mem::size_of::<Position>() == 400);
+
+impl Position {
+    fn total_liquidity(&self) -> u128 {
+        self.unlocked_liquidity
+            .saturating_add(self.vested_liquidity)
+            .saturating_add(self.permanent_locked_liquidity)
+    }
+
+    /// Base and quote fees the position would receive from a claim now:
+    /// the pending amounts plus fees accrued since its checkpoints.
+    fn pending_fees(&self, pool: &Pool) -> (u64, u64) {
+        let liquidity = self.total_liquidity();
+        let fee_a = pending_fee(
+            liquidity,
+            &pool.fee_a_per_liquidity,
+            &self.fee_a_per_token_checkpoint,
+        );
+        let fee_b = pending_fee(
+            liquidity,
+            &pool.fee_b_per_liquidity,
+            &self.fee_b_per_token_checkpoint,
+        );
+        (
+            self.fee_a_pending.saturating_add(fee_a),
+            self.fee_b_pending.saturating_add(fee_b),
+        )
+    }
 }
 
-#[account]
-#[derive(Default)]
-pub struct Pool {
-    pub token_mint_a: Pubkey,
-    pub token_mint_b: Pubkey,
-    pub collect_fee_mode: u8,
-    pub tick_current: i32,
-    pub liquidity: u128,
-    pub fee_growth_global_a_x64: u128,
-    pub fee_growth_global_b_x64: u128,
+/// `liquidity * (fee_per_liquidity - checkpoint) >> 128`, where both
+/// fee-per-liquidity values are little-endian U256 scaled by 2^128.
+fn pending_fee(liquidity: u128, fee_per_liquidity: &[u8; 32], checkpoint: &[u8; 32]) -> u64 {
+    let limbs = |bytes: &[u8; 32]| -> [u64; 4] {
+        std::array::from_fn(|i| u64::from_le_bytes(bytes[i * 8..i * 8 + 8].try_into().unwrap()))
+    };
+    let (current, checkpoint) = (limbs(fee_per_liquidity), limbs(checkpoint));
+
+    let mut delta = [0u64; 4];
+    let mut borrow = false;
+    for i in 0..4 {
+        let (diff, borrow_a) = current[i].overflowing_sub(checkpoint[i]);
+        let (diff, borrow_b) = diff.overflowing_sub(borrow as u64);
+        delta[i] = diff;
+        borrow = borrow_a || borrow_b;
+    }
+    if borrow {
+        return 0;
+    }
+
+    let mut product = [0u64; 6];
+    for (i, l) in [liquidity as u64, (liquidity >> 64) as u64]
+        .into_iter()
+        .enumerate()
+    {
+        let mut carry = 0u128;
+        for (j, d) in delta.into_iter().enumerate() {
+            let t = product[i + j] as u128 + l as u128 * d as u128 + carry;
+            product[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        product[i + 4] = carry as u64;
+    }
+    if product[3..].iter().any(|&limb| limb != 0) {
+        u64::MAX
+    } else {
+        product[2]
+    }
 }
 
-#[account]
-#[derive(Default)]
-pub struct Position {}
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeEstimate {
+    pub pending_base_fees: u64,
+    pub pending_quote_fees: u64,
+    pub carry_over: u64,
+}
 
-#[account]
-#[derive(Default)]
+/// The vesting schedule and recipient of a Streamflow stream. `cliff` is
+/// relative to `start_time`.
+#[derive(Clone, Copy, Debug, Default)]
 pub struct Stream {
     pub start_time: u64,
     pub deposited_amount: u64,
@@ -741,15 +2852,144 @@ pub struct Stream {
     pub cliff: u64,
     pub cliff_amount: u64,
     pub withdrawn_amount: u64,
+    pub recipient: Pubkey,
 }
 
 #[event]
 pub struct PolicyInitialized {
     pub vault: Pubkey,
+    pub pool: Pubkey,
+    pub quote_mint: Pubkey,
+    pub creator: Pubkey,
     pub y0: u64,
     pub investor_fee_share_bps: u16,
 }
 
+#[event]
+pub struct CreatorUpdated {
+    pub vault: Pubkey,
+    pub creator: Pubkey,
+    pub creator_ata: Option<Pubkey>,
+}
+
+#[event]
+pub struct CreatorSplitUpdated {
+    pub vault: Pubkey,
+    pub recipients: Vec<SplitRecipient>,
+    pub primary_index: u8,
+}
+
+#[event]
+pub struct OperatorFeeUpdated {
+    pub vault: Pubkey,
+    pub operator_fee_bps: u16,
+    pub operator_fee_recipient: Pubkey,
+    pub operator_fee_cap: Option<u64>,
+}
+
+#[event]
+pub struct OperatorFeeCollected {
+    pub vault: Pubkey,
+    pub day_ts: u64,
+    pub claimed: u64,
+    pub operator_fee_bps: u16,
+    pub amount: u64,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct CrankerRewardUpdated {
+    pub vault: Pubkey,
+    pub cranker_reward_fixed: u64,
+    pub cranker_reward_bps: u16,
+    pub cranker_reward_cap: u64,
+}
+
+#[event]
+pub struct TransferHookAccountsUpdated {
+    pub vault: Pubkey,
+    pub transfer_hook_accounts: u8,
+}
+
+#[event]
+pub struct NativePayoutsUpdated {
+    pub vault: Pubkey,
+    pub native_payouts: bool,
+}
+
+#[event]
+pub struct DistributionThresholdsUpdated {
+    pub vault: Pubkey,
+    pub min_claim_amount: u64,
+    pub min_distribution_amount: u64,
+}
+
+#[event]
+pub struct DistributionDeferred {
+    pub vault: Pubkey,
+    pub day_ts: u64,
+    pub pending_quote_fees: u64,
+    pub claimed: u64,
+    pub carry_over: u64,
+}
+
+#[event]
+pub struct AtaCreationUpdated {
+    pub vault: Pubkey,
+    pub auto_create_atas: bool,
+    pub ata_reimbursement: u64,
+    pub ata_reimbursement_cap: u64,
+}
+
+#[event]
+pub struct InvestorPayoutEscrowed {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct LiquidityAccountsUpdated {
+    pub vault: Pubkey,
+    pub token_a_account: Pubkey,
+    pub token_b_account: Pubkey,
+}
+
+#[event]
+pub struct LiquidityAdded {
+    pub vault: Pubkey,
+    pub position: Pubkey,
+    pub liquidity_delta: u128,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+}
+
+#[event]
+pub struct LiquidityRemoved {
+    pub vault: Pubkey,
+    pub position: Pubkey,
+    pub liquidity_delta: u128,
+    pub token_a_amount: u64,
+    pub token_b_amount: u64,
+}
+
+#[event]
+pub struct PositionPermanentlyLocked {
+    pub vault: Pubkey,
+    pub position: Pubkey,
+    pub liquidity: u128,
+}
+
+#[event]
+pub struct UnclaimedPayoutClaimed {
+    pub vault: Pubkey,
+    pub stream: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub transfer_fee_withheld: u64,
+}
+
 #[event]
 pub struct HonoraryPositionInitialized {
     pub vault: Pubkey,
@@ -760,6 +3000,7 @@ pub struct HonoraryPositionInitialized {
 pub struct QuoteFeesClaimed {
     pub vault: Pubkey,
     pub amount: u64,
+    pub transfer_fee_withheld: u64,
 }
 
 #[event]
@@ -767,6 +3008,8 @@ pub struct InvestorPayoutPage {
     pub vault: Pubkey,
     pub page_index: u16,
     pub amount: u64,
+    pub cranker_reward: u64,
+    pub transfer_fee_withheld: u64,
 }
 
 #[event]
@@ -778,6 +3021,7 @@ pub struct CreatorPayoutDayClosed {
     pub actual_distributed: u64,
     pub creator_received: u64,
     pub carry_over: u64,
+    pub transfer_fees_withheld: u64,
 }
 
 #[event]
@@ -806,28 +3050,66 @@ pub enum ErrorCode {
     InvalidVault,
     #[msg("Invalid tick range for quote-only fee position")]
     InvalidTickRange,
-}
-
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct CreateUncheckedWithPayerArgs {
-    pub start_time: u64,
-    pub net_amount_deposited: u64,
-    pub period: u64,
-    pub amount_per_period: u64,
-    pub cliff: u64,
-    pub cliff_amount: u64,
-    pub cancelable_by_sender: bool,
-    pub cancelable_by_recipient: bool,
-    pub automatic_withdrawal: bool,
-    pub transferable_by_sender: bool,
-    pub transferable_by_recipient: bool,
-    pub can_topup: bool,
-    pub stream_name: [u8; 64],
-    pub withdraw_frequency: u64,
-    pub recipient: Pubkey,
-    pub partner: Pubkey,
-    pub pausable: bool,
-    pub can_update_rate: bool,
+    #[msg("Pool does not match policy")]
+    InvalidPool,
+    #[msg("Treasury account does not match policy or is not owned by the position owner")]
+    InvalidTreasury,
+    #[msg("Creator token account does not match the policy creator")]
+    InvalidCreatorAccount,
+    #[msg("Signer is not the policy authority")]
+    Unauthorized,
+    #[msg("Creator split recipients are invalid or missing")]
+    InvalidCreatorSplit,
+    #[msg("Operator fee exceeds the maximum allowed")]
+    OperatorFeeTooHigh,
+    #[msg("Operator fee account missing or does not match policy")]
+    MissingOperatorFeeAccount,
+    #[msg("Cranker reward must be either a fixed amount or bps, not both")]
+    InvalidCrankerReward,
+    #[msg("Token program does not own the mint")]
+    InvalidTokenProgram,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Quote mint has a transfer hook but its extra accounts were not supplied")]
+    MissingTransferHookAccounts,
+    #[msg("Native payouts require a wrapped SOL quote mint")]
+    QuoteMintNotNative,
+    #[msg("Native payouts require the cranker, unwrap account, creator and system program")]
+    MissingNativeAccounts,
+    #[msg("Recipient does not match the stream recipient")]
+    InvalidRecipient,
+    #[msg("Native payouts and ATA auto-creation cannot both be enabled")]
+    InvalidPayoutMode,
+    #[msg("ATA creation requires the cranker, system program and associated token program")]
+    MissingAtaCreationAccounts,
+    #[msg("Unclaimed payout account does not match the stream")]
+    InvalidUnclaimedPayout,
+    #[msg("Escrowing a payout requires the cranker and system program")]
+    MissingEscrowAccounts,
+    #[msg("Position is not the vault's registered honorary position")]
+    InvalidPosition,
+    #[msg("Honorary position already initialized")]
+    PositionAlreadyInitialized,
+    #[msg("Honorary position liquidity is permanently locked")]
+    PositionPermanentlyLocked,
+    #[msg("Token account is not the authority's configured liquidity account")]
+    InvalidLiquidityAccount,
+    #[msg("Pool is disabled")]
+    PoolDisabled,
+    #[msg("Pool activation type is neither slot nor timestamp")]
+    InvalidActivationType,
+    #[msg("Pool minimum sqrt price is not below its maximum")]
+    InvalidPriceRange,
+    #[msg("Pool sqrt price is outside its price range")]
+    PriceOutOfRange,
+    #[msg("Distribution for this day was deferred")]
+    DistributionDeferred,
+    #[msg("Pool has not reached its activation point")]
+    PoolNotActivated,
+    #[msg("Stream account is not a Streamflow stream")]
+    InvalidStream,
+    #[msg("Liquidity deposit drew on treasury funds beyond the staged amount")]
+    TreasuryUnderfunded,
 }
 
 #[cfg(test)]
@@ -847,6 +3129,272 @@ mod tests {
         assert!(tick_lower < tick_upper, "Tick range must be valid");
     }
 
+    #[test]
+    fn test_pool_activation() {
+        let quote_mint = Pubkey::new_unique();
+        let mut pool: Pool = bytemuck::Zeroable::zeroed();
+        pool.token_b_mint = quote_mint;
+        pool.collect_fee_mode = COLLECT_FEE_MODE_ONLY_B;
+        pool.sqrt_min_price = 1 << 60;
+        pool.sqrt_price = 1 << 64;
+        pool.sqrt_max_price = 1 << 68;
+        pool.activation_point = 1_000;
+        let clock = |slot, unix_timestamp| Clock {
+            slot,
+            unix_timestamp,
+            ..Default::default()
+        };
+
+        pool.activation_type = ACTIVATION_TYPE_SLOT;
+        assert!(validate_quote_only_pool(&pool, &quote_mint, &clock(1_000, 0)).is_ok());
+        assert_eq!(
+            validate_quote_only_pool(&pool, &quote_mint, &clock(999, 5_000)).unwrap_err(),
+            error!(ErrorCode::PoolNotActivated)
+        );
+
+        pool.activation_type = ACTIVATION_TYPE_TIMESTAMP;
+        assert!(validate_quote_only_pool(&pool, &quote_mint, &clock(0, 1_000)).is_ok());
+        assert_eq!(
+            validate_quote_only_pool(&pool, &quote_mint, &clock(5_000, 999)).unwrap_err(),
+            error!(ErrorCode::PoolNotActivated)
+        );
+    }
+
+    #[test]
+    fn test_liquidity_changes_rejected_once_locked() {
+        let mut policy = Policy::default();
+        assert!(policy.require_unlocked().is_ok());
+        policy.locked_permanently = true;
+        assert_eq!(
+            policy.require_unlocked().unwrap_err(),
+            error!(ErrorCode::PositionPermanentlyLocked)
+        );
+    }
+
+    /// Streamflow `Contract` bytes written at the documented field offsets.
+    #[test]
+    fn test_streamflow_stream_layout() {
+        let mut data = vec![0u8; 1_100];
+        let mut put = |offset: usize, bytes: &[u8]| {
+            data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        let recipient = Pubkey::new_unique();
+        put(17, &250u64.to_le_bytes());
+        put(113, recipient.as_ref());
+        for (i, value) in [1_000u64, 10_000, 10, 100, 1_100, 500].iter().enumerate() {
+            put(409 + i * 8, &value.to_le_bytes());
+        }
+
+        let stream = Stream::parse(&data).unwrap();
+        assert_eq!(stream.recipient, recipient);
+        assert_eq!(stream.withdrawn_amount, 250);
+        assert_eq!((stream.start_time, stream.cliff), (1_000, 100));
+        assert_eq!(stream.unlocked_amount(1_100), 500);
+        assert_eq!(stream.unlocked_amount(1_120), 700);
+        assert_eq!(
+            Stream::parse(&data[..400]).unwrap_err(),
+            error!(ErrorCode::InvalidStream)
+        );
+
+        let load = |owner: &Pubkey| {
+            let key = Pubkey::new_unique();
+            let mut lamports = 0;
+            let mut data = data.clone();
+            let account = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                owner,
+                false,
+                0,
+            );
+            Stream::load(&account).map(|stream| stream.recipient)
+        };
+        assert_eq!(load(&STREAMFLOW_PROGRAM_ID).unwrap(), recipient);
+        assert_eq!(
+            load(&crate::ID).unwrap_err(),
+            error!(ErrorCode::InvalidStream)
+        );
+    }
+
+    #[test]
+    fn test_skipped_payouts_carry_over_once() {
+        let policy = Policy {
+            min_payout_lamports: 100,
+            ..Default::default()
+        };
+        let mut progress = Progress {
+            investor_intended_for_day: 1_000,
+            ..Default::default()
+        };
+        // The creator share has been paid out, so the treasury holds the
+        // investor share.
+        let mut treasury = progress.investor_intended_for_day;
+
+        // Paid, dust, and a native payout whose recipient cannot be funded.
+        let payouts = [(600, true), (50, true), (350, false)];
+        for (payout, deliverable) in payouts {
+            if payout >= policy.min_payout_lamports && deliverable {
+                treasury -= payout;
+                progress.actual_distributed += payout;
+            }
+        }
+        assert_eq!(progress.carry_over_undistributed(), 400);
+        assert_eq!(progress.carry_over, treasury);
+    }
+
+    #[test]
+    fn test_pool_quote_only_conditions() {
+        let quote_mint = Pubkey::new_unique();
+        let mut pool: Pool = bytemuck::Zeroable::zeroed();
+        pool.token_b_mint = quote_mint;
+        pool.collect_fee_mode = COLLECT_FEE_MODE_ONLY_B;
+        pool.activation_type = ACTIVATION_TYPE_TIMESTAMP;
+        pool.sqrt_min_price = 1 << 60;
+        pool.sqrt_price = 1 << 64;
+        pool.sqrt_max_price = 1 << 68;
+        let clock = Clock::default();
+        assert!(validate_quote_only_pool(&pool, &quote_mint, &clock).is_ok());
+
+        let expect = |pool: &Pool, mint: &Pubkey, code: ErrorCode| {
+            assert_eq!(
+                validate_quote_only_pool(pool, mint, &clock).unwrap_err(),
+                error!(code)
+            );
+        };
+        expect(&pool, &Pubkey::new_unique(), ErrorCode::InvalidQuoteMint);
+        expect(
+            &Pool {
+                collect_fee_mode: 0,
+                ..pool
+            },
+            &quote_mint,
+            ErrorCode::InvalidPoolConfig,
+        );
+        expect(
+            &Pool {
+                pool_status: 1,
+                ..pool
+            },
+            &quote_mint,
+            ErrorCode::PoolDisabled,
+        );
+        expect(
+            &Pool {
+                activation_type: 2,
+                ..pool
+            },
+            &quote_mint,
+            ErrorCode::InvalidActivationType,
+        );
+        expect(
+            &Pool {
+                sqrt_max_price: 1 << 60,
+                ..pool
+            },
+            &quote_mint,
+            ErrorCode::InvalidPriceRange,
+        );
+        expect(
+            &Pool {
+                sqrt_price: 1 << 69,
+                ..pool
+            },
+            &quote_mint,
+            ErrorCode::PriceOutOfRange,
+        );
+    }
+
+    /// The fixtures are synthesized by writing known values at the DAMM v2
+    /// field offsets, independently of the structs above.
+    #[test]
+    fn test_damm_v2_account_fixtures() {
+        fn parse<T>(bytes: &[u8], load: fn(&AccountInfo) -> Result<T>) -> Result<T> {
+            let key = Pubkey::new_unique();
+            let mut lamports = 0;
+            let mut data = bytes.to_vec();
+            let account = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                &DAMM_V2_PROGRAM_ID,
+                false,
+                0,
+            );
+            load(&account)
+        }
+
+        let pool_bytes = include_bytes!("../tests/fixtures/damm_v2_pool.bin");
+        assert_eq!(pool_bytes.len(), 8 + std::mem::size_of::<Pool>());
+        let pool = parse(pool_bytes, load_pool).unwrap();
+        assert_eq!(pool.pool_fees.base_fee.cliff_fee_numerator, 2_500_000);
+        assert_eq!(pool.pool_fees.protocol_fee_percent, 20);
+        assert_eq!(pool.token_a_mint, Pubkey::new_from_array([1; 32]));
+        assert_eq!(pool.token_b_mint, Pubkey::new_from_array([2; 32]));
+        assert_eq!(pool.token_b_vault, Pubkey::new_from_array([4; 32]));
+        assert_eq!(pool.liquidity, 1_000_000_000_000);
+        assert_eq!(pool.protocol_b_fee, 7);
+        assert_eq!(pool.sqrt_min_price, 4_295_048_016);
+        assert_eq!(pool.sqrt_max_price, 79_226_673_521_066_979_257_578_248_091);
+        assert_eq!(pool.sqrt_price, 1 << 64);
+        assert_eq!(pool.activation_point, 1_700_000_000);
+        assert_eq!(pool.activation_type, ACTIVATION_TYPE_TIMESTAMP);
+        assert_eq!(pool.collect_fee_mode, COLLECT_FEE_MODE_ONLY_B);
+        assert_eq!(pool.fee_b_per_liquidity[16], 5);
+        assert_eq!(pool.permanent_lock_liquidity, 250_000_000_000);
+        assert_eq!(pool.creator, Pubkey::new_from_array([9; 32]));
+        let clock = Clock {
+            unix_timestamp: 1_700_000_000,
+            ..Default::default()
+        };
+        assert!(validate_quote_only_pool(&pool, &Pubkey::new_from_array([2; 32]), &clock).is_ok());
+
+        let position_bytes = include_bytes!("../tests/fixtures/damm_v2_position.bin");
+        assert_eq!(position_bytes.len(), 8 + std::mem::size_of::<Position>());
+        let position = parse(position_bytes, load_position).unwrap();
+        assert_eq!(position.pool, Pubkey::new_from_array([5; 32]));
+        assert_eq!(position.nft_mint, Pubkey::new_from_array([6; 32]));
+        assert_eq!(position.fee_b_per_token_checkpoint[16], 3);
+        assert_eq!(position.fee_b_pending, 1_500);
+        assert_eq!(position.total_liquidity(), 850_000_000_000);
+        assert_eq!(position.metrics.total_claimed_b_fee, 42_000);
+        assert_eq!(position.pending_fees(&pool), (0, 1_700_000_001_500));
+
+        assert!(parse(position_bytes, load_pool).is_err());
+        assert!(parse(pool_bytes, load_position).is_err());
+    }
+
+    #[test]
+    fn test_pending_fee_math() {
+        let u256 = |value: u128, shift: u32| {
+            let mut bytes = [0u8; 32];
+            let (lo, hi) = if shift >= 128 {
+                (0, value << (shift - 128))
+            } else {
+                (
+                    value << shift,
+                    if shift == 0 {
+                        0
+                    } else {
+                        value >> (128 - shift)
+                    },
+                )
+            };
+            bytes[..16].copy_from_slice(&lo.to_le_bytes());
+            bytes[16..].copy_from_slice(&hi.to_le_bytes());
+            bytes
+        };
+
+        assert_eq!(pending_fee(1_000, &u256(7, 128), &u256(2, 128)), 5_000);
+        assert_eq!(pending_fee(1_000, &u256(1, 127), &[0; 32]), 500);
+        assert_eq!(pending_fee(1 << 100, &u256(1, 128), &[0; 32]), u64::MAX);
+        assert_eq!(pending_fee(1_000, &[0; 32], &u256(1, 128)), 0);
+    }
+
     #[test]
     fn test_distribution_math() {
         let total_locked: u64 = 1000000;
@@ -859,6 +3407,14 @@ mod tests {
         assert_eq!(payout, 2500, "Should receive 25% of fees");
     }
 
+    #[test]
+    fn test_investor_weight_and_payout() {
+        assert_eq!(investor_weight(250_000, 1_000_000), 250_000);
+        assert_eq!(investor_weight(250_000, 0), 0);
+        assert_eq!(investor_payout(10_000, 250_000), 2500);
+        assert_eq!(investor_payout(u64::MAX, 1_000_000), u64::MAX);
+    }
+
     #[test]
     fn test_24hour_gate() {
         let last_distribution: u64 = 1000000;
@@ -912,6 +3468,38 @@ mod tests {
     }
 
     #[test]
+    fn test_investor_intended() {
+        let locked_total: u64 = 750_000_000_000;
+        let mut policy = Policy {
+            y0: 1_000_000_000_000,
+            investor_fee_share_bps: 8000,
+            daily_cap: Some(700),
+            ..Default::default()
+        };
+        assert_eq!(policy.f_locked_bps(locked_total), 7500);
+        assert_eq!(policy.eligible_bps(locked_total), 7500);
+        assert_eq!(policy.investor_intended(1000, locked_total), 700);
+        policy.daily_cap = None;
+        assert_eq!(policy.investor_intended(1000, locked_total), 750);
+        policy.y0 = 0;
+        assert_eq!(policy.investor_intended(1000, locked_total), 0);
+    }
+
+    #[test]
+    fn test_locked_fraction_clamped() {
+        let policy = Policy {
+            y0: 1_000,
+            investor_fee_share_bps: 10000,
+            ..Default::default()
+        };
+        assert_eq!(policy.f_locked_bps(u64::MAX), 10000);
+        assert_eq!(policy.eligible_bps(u64::MAX), 10000);
+        assert_eq!(policy.f_locked_bps(10_000_000), 10000);
+        assert_eq!(policy.investor_intended(1000, u64::MAX), 1000);
+    }
+
+    #[test]
+    #[allow(unused_variables, clippy::manual_checked_ops)]
     fn test_all_unlocked_scenario() {
         let locked_total: u64 = 0;
         let claimed_fees: u64 = 100000000000;
@@ -931,6 +3519,352 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_creator_split_residue_to_primary() {
+        let mut split = CreatorSplit {
+            primary_index: 1,
+            recipient_count: 3,
+            ..Default::default()
+        };
+        split.recipients[0].bps = 3333;
+        split.recipients[1].bps = 3333;
+        split.recipients[2].bps = 3334;
+
+        let amounts = split.split_amount(100);
+        assert_eq!(amounts[..3], [33, 34, 33]);
+        assert_eq!(amounts.iter().sum::<u64>(), 100);
+    }
+
+    #[test]
+    fn test_operator_fee_capped() {
+        let mut policy = Policy {
+            operator_fee_bps: 250,
+            ..Default::default()
+        };
+        assert_eq!(policy.operator_fee(1_000_000), 25_000);
+
+        policy.operator_fee_cap = Some(10_000);
+        assert_eq!(policy.operator_fee(1_000_000), 10_000);
+        assert_eq!(policy.operator_fee(100_000), 2_500);
+    }
+
+    #[test]
+    fn test_cranker_reward_budget() {
+        let policy = Policy {
+            cranker_reward_fixed: 400,
+            cranker_reward_cap: 1_000,
+            ..Default::default()
+        };
+        assert_eq!(policy.cranker_reward(50_000, 0, 10_000), 400);
+        assert_eq!(policy.cranker_reward(50_000, 800, 10_000), 200);
+        assert_eq!(policy.cranker_reward(50_000, 0, 300), 300);
+
+        let policy = Policy {
+            cranker_reward_bps: 10,
+            cranker_reward_cap: 1_000,
+            ..Default::default()
+        };
+        assert_eq!(policy.cranker_reward(50_000, 0, 10_000), 50);
+    }
+
+    #[test]
+    fn test_transfer_fee_config_detection() {
+        use anchor_spl::token_2022::spl_token_2022::extension::{
+            transfer_fee::TransferFee, BaseStateWithExtensionsMut, ExtensionType,
+            StateWithExtensionsMut,
+        };
+
+        let len = ExtensionType::try_calculate_account_len::<MintState>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .unwrap();
+        let mut data = vec![0u8; len];
+        {
+            let mut state =
+                StateWithExtensionsMut::<MintState>::unpack_uninitialized(&mut data).unwrap();
+            let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+            let fee = TransferFee {
+                epoch: 0.into(),
+                maximum_fee: 1_000.into(),
+                transfer_fee_basis_points: 100.into(),
+            };
+            config.older_transfer_fee = fee;
+            config.newer_transfer_fee = fee;
+            state.base.is_initialized = true;
+            state.base.decimals = 6;
+            state.pack_base();
+            state.init_account_type().unwrap();
+        }
+
+        let key = Pubkey::new_unique();
+        let owner = Token2022::id();
+        let mut lamports = 0;
+        let mint = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        let config = transfer_fee_config(&mint).unwrap().unwrap();
+        assert_eq!(config.calculate_epoch_fee(0, 10_000), Some(100));
+        assert_eq!(config.calculate_epoch_fee(0, 1_000_000), Some(1_000));
+
+        let legacy_owner = anchor_spl::token::ID;
+        let mut lamports = 0;
+        let mut legacy_data = vec![0u8; MintState::LEN];
+        let legacy = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut legacy_data,
+            &legacy_owner,
+            false,
+            0,
+        );
+        assert!(transfer_fee_config(&legacy).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_ata_reimbursement_capped() {
+        let policy = Policy {
+            auto_create_atas: true,
+            ata_reimbursement: 2_000,
+            ata_reimbursement_cap: 5_000,
+            ..Default::default()
+        };
+        assert_eq!(policy.investor_entry_len(), 4);
+        assert_eq!(policy.ata_reimbursement(2, 0, 100_000), 4_000);
+        assert_eq!(policy.ata_reimbursement(2, 4_000, 100_000), 1_000);
+        assert_eq!(policy.ata_reimbursement(2, 0, 3_000), 3_000);
+        assert_eq!(Policy::default().investor_entry_len(), 3);
+
+        // Across pages, the total stays within the creator share even when
+        // the daily cap exceeds it.
+        let policy = Policy {
+            ata_reimbursement_cap: 10_000,
+            ..policy
+        };
+        let creator_share = 5_000;
+        let mut paid = 0;
+        for _ in 0..4 {
+            paid += policy.ata_reimbursement(2, paid, creator_share - paid);
+        }
+        assert_eq!(paid, creator_share);
+    }
+
+    #[test]
+    fn test_token_account_owner() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0u8; TokenAccountState::LEN];
+        TokenAccountState {
+            mint,
+            owner,
+            state: AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let token_program = anchor_spl::token::ID;
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &token_program,
+            false,
+            0,
+        );
+        assert!(is_deliverable(&account, &mint, &token_program).unwrap());
+        assert_eq!(token_account_owner(&account).unwrap(), owner);
+    }
+
+    #[test]
+    fn test_native_payout_recipients() {
+        let rent = Rent::default();
+        let minimum = rent.minimum_balance(0);
+        let check = |mut lamports: u64, amount: u64| {
+            let key = Pubkey::new_unique();
+            let owner = System::id();
+            let mut data = [];
+            let account = AccountInfo::new(
+                &key,
+                false,
+                true,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+            can_receive_lamports(&account, amount, &rent)
+        };
+
+        assert!(!check(0, minimum - 1));
+        assert!(check(0, minimum));
+        assert!(check(1, 1));
+    }
+
+    #[test]
+    fn test_undeliverable_accounts() {
+        let mint = Pubkey::new_unique();
+        let token_program = anchor_spl::token::ID;
+        let check = |state: AccountState, account_mint: Pubkey, owner: Pubkey| {
+            let mut data = vec![0u8; TokenAccountState::LEN];
+            TokenAccountState::pack(
+                TokenAccountState {
+                    mint: account_mint,
+                    owner: Pubkey::new_unique(),
+                    state,
+                    ..Default::default()
+                },
+                &mut data,
+            )
+            .unwrap();
+            let key = Pubkey::new_unique();
+            let mut lamports = 0;
+            let account = AccountInfo::new(
+                &key,
+                false,
+                true,
+                &mut lamports,
+                &mut data,
+                &owner,
+                false,
+                0,
+            );
+            is_deliverable(&account, &mint, &token_program).unwrap()
+        };
+
+        assert!(check(AccountState::Initialized, mint, token_program));
+        assert!(!check(AccountState::Frozen, mint, token_program));
+        assert!(!check(
+            AccountState::Initialized,
+            Pubkey::new_unique(),
+            token_program
+        ));
+        assert!(!check(AccountState::Initialized, mint, Pubkey::default()));
+    }
+
+    #[test]
+    fn test_distribution_deferral_thresholds() {
+        let policy = Policy {
+            min_claim_amount: 1_000,
+            min_distribution_amount: 5_000,
+            ..Default::default()
+        };
+        assert!(policy.skips_claim(999));
+        assert!(!policy.skips_claim(1_000));
+        assert!(policy.defers_distribution(true, 10_000));
+        assert!(policy.defers_distribution(false, 4_999));
+        assert!(!policy.defers_distribution(false, 5_000));
+        assert!(!Policy::default().skips_claim(0));
+        assert!(!Policy::default().defers_distribution(false, 0));
+    }
+
+    #[test]
+    fn test_cpi_discriminators() {
+        use anchor_lang::solana_program::hash::hash;
+        use anchor_lang::{Discriminator, InstructionData};
+
+        fn sighash(name: &str) -> Vec<u8> {
+            hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec()
+        }
+
+        let cases: [(&str, &[u8]); 7] = [
+            (
+                "create_position",
+                damm_v2::instruction::CreatePosition::DISCRIMINATOR,
+            ),
+            (
+                "claim_position_fee",
+                damm_v2::instruction::ClaimPositionFee::DISCRIMINATOR,
+            ),
+            (
+                "add_liquidity",
+                damm_v2::instruction::AddLiquidity::DISCRIMINATOR,
+            ),
+            (
+                "remove_liquidity",
+                damm_v2::instruction::RemoveLiquidity::DISCRIMINATOR,
+            ),
+            (
+                "remove_all_liquidity",
+                damm_v2::instruction::RemoveAllLiquidity::DISCRIMINATOR,
+            ),
+            (
+                "permanent_lock_position",
+                damm_v2::instruction::PermanentLockPosition::DISCRIMINATOR,
+            ),
+            (
+                "createUncheckedWithPayer",
+                streamflow::instruction::CreateUncheckedWithPayer::DISCRIMINATOR,
+            ),
+        ];
+        for (name, discriminator) in cases {
+            assert_eq!(sighash(name), discriminator, "{name}");
+        }
+
+        // The owner PDA signs the fee claim, and add_liquidity drops the
+        // leading pool authority.
+        let accounts = || damm_v2::accounts::ModifyPosition {
+            pool: Pubkey::new_unique(),
+            position: Pubkey::new_unique(),
+            token_a_account: Pubkey::new_unique(),
+            token_b_account: Pubkey::new_unique(),
+            token_a_vault: Pubkey::new_unique(),
+            token_b_vault: Pubkey::new_unique(),
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            position_nft_account: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            token_a_program: Pubkey::new_unique(),
+            token_b_program: Pubkey::new_unique(),
+            event_authority: Pubkey::new_unique(),
+        };
+        let claim = damm_v2::accounts::ClaimPositionFee(accounts()).to_account_metas(None);
+        assert_eq!(claim.len(), 15);
+        assert!(claim[10].is_signer && !claim[1].is_writable);
+        let add = damm_v2::accounts::AddLiquidity(accounts()).to_account_metas(None);
+        assert_eq!(add.len(), 14);
+        assert!(add[9].is_signer && add[0].is_writable);
+
+        let ix = damm_v2::instruction(
+            &damm_v2::accounts::AddLiquidity(accounts()),
+            &damm_v2::instruction::AddLiquidity {
+                liquidity_delta: 1,
+                token_a_amount_threshold: 2,
+                token_b_amount_threshold: 3,
+            },
+        );
+        assert_eq!(ix.program_id, DAMM_V2_PROGRAM_ID);
+        assert_eq!(ix.data.len(), 8 + 16 + 8 + 8);
+        assert_eq!(
+            damm_v2::instruction::CreatePosition {}.data(),
+            damm_v2::instruction::CreatePosition::DISCRIMINATOR
+        );
+        let create = damm_v2::accounts::CreatePosition {
+            owner: Pubkey::new_unique(),
+            position_nft_mint: Pubkey::new_unique(),
+            position_nft_account: Pubkey::new_unique(),
+            pool: Pubkey::new_unique(),
+            position: Pubkey::new_unique(),
+            payer: Pubkey::new_unique(),
+            token_program: TOKEN22_PROGRAM_ID,
+            event_authority: Pubkey::new_unique(),
+        }
+        .to_account_metas(None);
+        assert!(create[1].is_signer && create[1].is_writable);
+    }
+
     #[test]
     fn test_pagination_state() {
         let mut cursor: u16 = 0;
@@ -0,0 +1,135 @@
+//! Typed instruction builders for the Streamflow timelock program, shaped
+//! like the DAMM v2 builders in `damm_v2`, and the layout of its stream
+//! accounts.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+
+pub use crate::STREAMFLOW_PROGRAM_ID as ID;
+
+/// Builds a Streamflow instruction from typed accounts and arguments.
+pub fn instruction(accounts: &impl ToAccountMetas, args: &impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: args.data(),
+    }
+}
+
+pub mod accounts {
+    use super::*;
+
+    pub struct CreateUncheckedWithPayer {
+        pub payer: Pubkey,
+        pub sender: Pubkey,
+        pub sender_tokens: Pubkey,
+        pub metadata: Pubkey,
+        pub escrow_tokens: Pubkey,
+        pub withdrawor: Pubkey,
+        pub mint: Pubkey,
+        pub fee_oracle: Pubkey,
+        pub token_program: Pubkey,
+    }
+
+    impl ToAccountMetas for CreateUncheckedWithPayer {
+        fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+            vec![
+                AccountMeta::new(self.payer, true),
+                AccountMeta::new(self.sender, true),
+                AccountMeta::new(self.sender_tokens, false),
+                AccountMeta::new(self.metadata, false),
+                AccountMeta::new(self.escrow_tokens, false),
+                AccountMeta::new(self.withdrawor, false),
+                AccountMeta::new_readonly(self.mint, false),
+                AccountMeta::new_readonly(self.fee_oracle, false),
+                AccountMeta::new_readonly(anchor_lang::solana_program::sysvar::rent::ID, false),
+                AccountMeta::new_readonly(ID, false),
+                AccountMeta::new_readonly(self.token_program, false),
+                AccountMeta::new_readonly(anchor_lang::system_program::ID, false),
+            ]
+        }
+    }
+}
+
+pub mod instruction {
+    use super::*;
+    use anchor_lang::Discriminator;
+
+    #[derive(AnchorSerialize, AnchorDeserialize)]
+    pub struct CreateUncheckedWithPayer {
+        pub start_time: u64,
+        pub net_amount_deposited: u64,
+        pub period: u64,
+        pub amount_per_period: u64,
+        pub cliff: u64,
+        pub cliff_amount: u64,
+        pub cancelable_by_sender: bool,
+        pub cancelable_by_recipient: bool,
+        pub automatic_withdrawal: bool,
+        pub transferable_by_sender: bool,
+        pub transferable_by_recipient: bool,
+        pub can_topup: bool,
+        pub stream_name: [u8; 64],
+        pub withdraw_frequency: u64,
+        pub recipient: Pubkey,
+        pub partner: Pubkey,
+        pub pausable: bool,
+        pub can_update_rate: bool,
+    }
+
+    // Streamflow's IDL names the instruction in camelCase, so this is the
+    // sighash of `global:createUncheckedWithPayer`.
+    impl Discriminator for CreateUncheckedWithPayer {
+        const DISCRIMINATOR: &'static [u8] = &[230, 9, 241, 173, 57, 159, 239, 202];
+    }
+
+    impl InstructionData for CreateUncheckedWithPayer {}
+}
+
+pub mod state {
+    use super::*;
+
+    /// Leading fields of Streamflow's `Contract` stream account, in Borsh
+    /// order. The account has no discriminator, and the fields after
+    /// `ix.cliff_amount` are not needed, so they are left undecoded.
+    #[derive(AnchorDeserialize)]
+    pub struct Contract {
+        pub magic: u64,
+        pub version: u8,
+        pub created_at: u64,
+        pub amount_withdrawn: u64,
+        pub canceled_at: u64,
+        pub end_time: u64,
+        pub last_withdrawn_at: u64,
+        pub sender: Pubkey,
+        pub sender_tokens: Pubkey,
+        pub recipient: Pubkey,
+        pub recipient_tokens: Pubkey,
+        pub mint: Pubkey,
+        pub escrow_tokens: Pubkey,
+        pub streamflow_treasury: Pubkey,
+        pub streamflow_treasury_tokens: Pubkey,
+        pub streamflow_fee_total: u64,
+        pub streamflow_fee_withdrawn: u64,
+        pub streamflow_fee_percent: f32,
+        pub partner: Pubkey,
+        pub partner_tokens: Pubkey,
+        pub partner_fee_total: u64,
+        pub partner_fee_withdrawn: u64,
+        pub partner_fee_percent: f32,
+        pub ix: Schedule,
+    }
+
+    /// Leading fields of the `CreateParams` a stream was created with.
+    /// `start_time` and `cliff` are unix timestamps.
+    #[derive(AnchorDeserialize)]
+    pub struct Schedule {
+        pub start_time: u64,
+        pub net_amount_deposited: u64,
+        pub period: u64,
+        pub amount_per_period: u64,
+        pub cliff: u64,
+        pub cliff_amount: u64,
+    }
+}
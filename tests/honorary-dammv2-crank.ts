@@ -7,6 +7,7 @@ import {
   SystemProgram,
   LAMPORTS_PER_SOL,
 } from "@solana/web3.js";
+import { createMint, createAccount } from "@solana/spl-token";
 import { assert } from "chai";
 
 describe("honorary-dammv2-crank", () => {
@@ -26,12 +27,16 @@ describe("honorary-dammv2-crank", () => {
     "HLnpSz9h2S4hiLQ43rnSD9XkcUThA7B8hQMKmDaiTLcC"
   );
 
+  let vaultKeypair: Keypair;
   let vault: PublicKey;
   let ownerPda: PublicKey;
   let policy: PublicKey;
   let progress: PublicKey;
+  let pool: PublicKey;
+  let quoteMint: PublicKey;
   let baseTreasury: PublicKey;
   let quoteTreasury: PublicKey;
+  let creator: PublicKey;
 
   const Y0 = new BN(1000000000000);
   const INVESTOR_FEE_SHARE_BPS = 5000;
@@ -39,7 +44,8 @@ describe("honorary-dammv2-crank", () => {
   const MIN_PAYOUT_LAMPORTS = new BN(1000000);
 
   before(async () => {
-    vault = Keypair.generate().publicKey;
+    vaultKeypair = Keypair.generate();
+    vault = vaultKeypair.publicKey;
 
     [ownerPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("investor_fee_pos_owner"), vault.toBuffer()],
@@ -56,14 +62,36 @@ describe("honorary-dammv2-crank", () => {
       program.programId
     );
 
-    [baseTreasury] = PublicKey.findProgramAddressSync(
-      [Buffer.from("base_treasury"), vault.toBuffer()],
-      program.programId
+    // DAMM v2 pool fixture preloaded through Anchor.toml.
+    pool = new PublicKey("3AcsiFywK3u3gHD8eEX6NpYGzutrCmqhhQJzr1cVuTyh");
+    creator = Keypair.generate().publicKey;
+
+    const payer = (provider.wallet as any).payer;
+    const baseMint = await createMint(
+      provider.connection,
+      payer,
+      provider.wallet.publicKey,
+      null,
+      6
     );
-
-    [quoteTreasury] = PublicKey.findProgramAddressSync(
-      [Buffer.from("quote_treasury"), vault.toBuffer()],
-      program.programId
+    quoteMint = await createMint(
+      provider.connection,
+      payer,
+      provider.wallet.publicKey,
+      null,
+      6
+    );
+    baseTreasury = await createAccount(
+      provider.connection,
+      payer,
+      baseMint,
+      ownerPda
+    );
+    quoteTreasury = await createAccount(
+      provider.connection,
+      payer,
+      quoteMint,
+      ownerPda
     );
   });
 
@@ -74,18 +102,33 @@ describe("honorary-dammv2-crank", () => {
           Y0,
           INVESTOR_FEE_SHARE_BPS,
           DAILY_CAP,
-          MIN_PAYOUT_LAMPORTS
+          MIN_PAYOUT_LAMPORTS,
+          null
         )
         .accounts({
           vault,
           policy,
+          ownerPda,
+          pool,
+          quoteMint,
+          baseTreasury,
+          quoteTreasury,
+          creator,
           payer: provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .signers([vaultKeypair])
         .rpc();
 
       const policyAccount = await program.account.policy.fetch(policy);
       assert.equal(policyAccount.vault.toBase58(), vault.toBase58());
+      assert.equal(policyAccount.pool.toBase58(), pool.toBase58());
+      assert.equal(policyAccount.quoteMint.toBase58(), quoteMint.toBase58());
+      assert.equal(
+        policyAccount.quoteTreasury.toBase58(),
+        quoteTreasury.toBase58()
+      );
+      assert.equal(policyAccount.creator.toBase58(), creator.toBase58());
       assert.equal(policyAccount.y0.toString(), Y0.toString());
       assert.equal(policyAccount.investorFeeShareBps, INVESTOR_FEE_SHARE_BPS);
       assert.equal(policyAccount.dailyCap.toString(), DAILY_CAP.toString());
@@ -102,20 +145,110 @@ describe("honorary-dammv2-crank", () => {
             Y0,
             INVESTOR_FEE_SHARE_BPS,
             DAILY_CAP,
-            MIN_PAYOUT_LAMPORTS
+            MIN_PAYOUT_LAMPORTS,
+            null
           )
           .accounts({
             vault,
             policy,
+            ownerPda,
+            pool,
+            quoteMint,
+            baseTreasury,
+            quoteTreasury,
+            creator,
             payer: provider.wallet.publicKey,
             systemProgram: SystemProgram.programId,
           })
+          .signers([vaultKeypair])
           .rpc();
         assert.fail("Should not allow double initialization");
       } catch (error) {
         assert.ok(error);
       }
     });
+
+    it("requires the vault to sign policy initialization", async () => {
+      const otherVault = Keypair.generate().publicKey;
+      const [otherPolicy] = PublicKey.findProgramAddressSync(
+        [Buffer.from("policy"), otherVault.toBuffer()],
+        program.programId
+      );
+      const [otherOwnerPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("investor_fee_pos_owner"), otherVault.toBuffer()],
+        program.programId
+      );
+      try {
+        await program.methods
+          .initializePolicy(
+            Y0,
+            INVESTOR_FEE_SHARE_BPS,
+            DAILY_CAP,
+            MIN_PAYOUT_LAMPORTS,
+            null
+          )
+          .accounts({
+            vault: otherVault,
+            policy: otherPolicy,
+            ownerPda: otherOwnerPda,
+            pool,
+            quoteMint,
+            baseTreasury,
+            quoteTreasury,
+            creator,
+            payer: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+      } catch (error) {
+        assert.match(String(error), /Missing signature/);
+        return;
+      }
+      assert.fail("Should require the vault signature");
+    });
+  });
+
+  describe("Creator Management", () => {
+    it("rotates the creator wallet", async () => {
+      const newCreator = Keypair.generate().publicKey;
+      const newCreatorAta = Keypair.generate().publicKey;
+
+      await program.methods
+        .setCreator(newCreator, newCreatorAta)
+        .accounts({
+          vault,
+          policy,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const policyAccount = await program.account.policy.fetch(policy);
+      assert.equal(policyAccount.creator.toBase58(), newCreator.toBase58());
+      assert.equal(
+        policyAccount.creatorAta.toBase58(),
+        newCreatorAta.toBase58()
+      );
+    });
+
+    it("rejects creator rotation from non-authority", async () => {
+      const intruder = Keypair.generate();
+      try {
+        await program.methods
+          .setCreator(intruder.publicKey, null)
+          .accounts({
+            vault,
+            policy,
+            authority: intruder.publicKey,
+          })
+          .signers([intruder])
+          .rpc();
+      } catch (error) {
+        assert.instanceOf(error, anchor.AnchorError);
+        assert.equal(error.error.errorCode.code, "Unauthorized");
+        return;
+      }
+      assert.fail("Should reject non-authority signer");
+    });
   });
 
   describe("Progress Tracking", () => {
@@ -294,8 +427,6 @@ describe("honorary-dammv2-crank", () => {
         { name: "investor_fee_pos_owner", pda: ownerPda },
         { name: "policy", pda: policy },
         { name: "progress", pda: progress },
-        { name: "base_treasury", pda: baseTreasury },
-        { name: "quote_treasury", pda: quoteTreasury },
       ];
 
       for (const seed of seeds) {
@@ -13,6 +13,7 @@ import {
   createAccount,
   mintTo,
   getAccount,
+  TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
 import { assert } from "chai";
 
@@ -35,10 +36,12 @@ describe("honorary-dammv2-crank E2E", () => {
 
   let baseMint: PublicKey;
   let quoteMint: PublicKey;
+  let vaultKeypair: Keypair;
   let vault: PublicKey;
   let ownerPda: PublicKey;
   let policy: PublicKey;
   let progress: PublicKey;
+  let pool: PublicKey;
   let baseTreasury: PublicKey;
   let quoteTreasury: PublicKey;
   let creatorQuoteAta: PublicKey;
@@ -59,7 +62,10 @@ describe("honorary-dammv2-crank E2E", () => {
   const MIN_PAYOUT = new BN(1000);
 
   before("Setup", async () => {
-    vault = Keypair.generate().publicKey;
+    vaultKeypair = Keypair.generate();
+    vault = vaultKeypair.publicKey;
+    // DAMM v2 pool fixture preloaded through Anchor.toml.
+    pool = new PublicKey("3AcsiFywK3u3gHD8eEX6NpYGzutrCmqhhQJzr1cVuTyh");
 
     [ownerPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("investor_fee_pos_owner"), vault.toBuffer()],
@@ -124,18 +130,35 @@ describe("honorary-dammv2-crank E2E", () => {
   describe("Initialization", () => {
     it("initializes policy", async () => {
       await program.methods
-        .initializePolicy(Y0, INVESTOR_FEE_SHARE_BPS, DAILY_CAP, MIN_PAYOUT)
+        .initializePolicy(
+          Y0,
+          INVESTOR_FEE_SHARE_BPS,
+          DAILY_CAP,
+          MIN_PAYOUT,
+          creatorQuoteAta
+        )
         .accounts({
           vault,
           policy,
+          ownerPda,
+          pool,
+          quoteMint,
+          baseTreasury,
+          quoteTreasury,
+          creator: creator.publicKey,
           payer: provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .signers([vaultKeypair])
         .rpc();
 
       const policyAccount = await program.account.policy.fetch(policy);
       assert.equal(policyAccount.vault.toBase58(), vault.toBase58());
       assert.equal(policyAccount.y0.toString(), Y0.toString());
+      assert.equal(
+        policyAccount.creator.toBase58(),
+        creator.publicKey.toBase58()
+      );
     });
 
     it("initializes progress", async () => {
@@ -166,7 +189,7 @@ describe("honorary-dammv2-crank E2E", () => {
           quoteTreasury,
           payer: provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
-          tokenProgram: TOKEN22_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
         .rpc();
     });